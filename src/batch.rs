@@ -0,0 +1,106 @@
+//! Bounded-concurrency batch fetching.
+use std::future::Future;
+
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::ClientError;
+
+/// The default maximum number of requests in flight for a batch.
+pub(crate) const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Fetch a batch of IDs, yielding results as they complete.
+///
+/// # Args
+///
+/// * `ids` - The IDs to fetch.
+/// * `concurrency` - The maximum number of fetches in flight.
+/// * `fetch` - Fetches a single ID.
+///
+/// # Returns
+///
+/// A stream of each ID paired with its result, in completion order.
+pub(crate) fn batch_unordered<T, F, Fut>(
+    ids: Vec<u32>,
+    concurrency: usize,
+    fetch: F,
+) -> impl Stream<Item = (u32, Result<T, ClientError>)>
+where
+    F: Fn(u32) -> Fut,
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    stream::iter(ids)
+        .map(move |id| {
+            let future = fetch(id);
+            async move { (id, future.await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+}
+
+/// Fetch a batch of IDs, collecting results in input order.
+///
+/// # Args
+///
+/// * `ids` - The IDs to fetch.
+/// * `concurrency` - The maximum number of fetches in flight.
+/// * `fetch` - Fetches a single ID.
+///
+/// # Returns
+///
+/// Each ID paired with its result, in the same order as `ids`.
+pub(crate) async fn batch_ordered<T, F, Fut>(
+    ids: Vec<u32>,
+    concurrency: usize,
+    fetch: F,
+) -> Vec<(u32, Result<T, ClientError>)>
+where
+    F: Fn(u32) -> Fut,
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    stream::iter(ids)
+        .map(move |id| {
+            let future = fetch(id);
+            async move { (id, future.await) }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_ordered_bounded() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let results = batch_ordered(vec![5, 1, 4, 2, 3], 2, |id| {
+            let (in_flight, peak) = (in_flight.clone(), peak.clone());
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(u64::from(id) * 5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if id == 4 {
+                    Err(ClientError::RateLimited { retry_after: None })
+                } else {
+                    Ok(id * 10)
+                }
+            }
+        })
+        .await;
+        let ids = results.iter().map(|(id, _)| *id).collect::<Vec<u32>>();
+        assert_eq!(ids, vec![5, 1, 4, 2, 3]);
+        assert!(results[2].1.is_err());
+        assert_eq!(results[0].1.as_ref().unwrap(), &50);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
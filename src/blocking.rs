@@ -0,0 +1,434 @@
+//! A blocking client for synchronous code.
+//!
+//! The blocking [`Client`] wraps an async [`crate::Client`] and drives it on its own runtime,
+//! so it shares the same configuration, data models, and errors.
+//!
+//! **Note**: the blocking [`Client`] must not be used from within an async runtime,
+//! since blocking on a future from inside an async context panics.
+use std::{pin::Pin, sync::Arc};
+
+use futures::stream::{Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::runtime::Runtime;
+
+use crate::{
+    models::*, stats::Stats, ArtistSongsSort, Client as AsyncClient, ClientError,
+    Method, ReferentAssociation, TextFormat,
+};
+
+/// A blocking HTTP client for interacting with the Genius API.
+///
+/// Built with [`crate::ClientBuilder::build_blocking`].
+/// Like the async [`crate::Client`], this can be cloned freely.
+#[derive(Debug, Clone)]
+pub struct Client {
+    // internal async client
+    inner: AsyncClient,
+    // runtime that drives the async client
+    runtime: Arc<Runtime>,
+}
+
+/// A blocking iterator over a paginated stream.
+struct PageIter<S> {
+    // internal stream
+    stream: Pin<Box<S>>,
+    // runtime that drives the stream
+    runtime: Arc<Runtime>,
+}
+
+impl<S: Stream> Iterator for PageIter<S> {
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+impl Client {
+    /// Create a new blocking [`Client`].
+    ///
+    /// # Args
+    ///
+    /// * `inner` - The async client to wrap.
+    /// * `runtime` - The runtime that drives the async client.
+    ///
+    /// # Returns
+    ///
+    /// A new blocking [`Client`].
+    pub(crate) fn new(inner: AsyncClient, runtime: Runtime) -> Self {
+        Client {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// Wrap a stream in a blocking iterator.
+    ///
+    /// # Args
+    ///
+    /// * `stream` - The stream to wrap.
+    ///
+    /// # Returns
+    ///
+    /// A blocking iterator over the stream.
+    fn iter<S: Stream>(&self, stream: S) -> PageIter<S> {
+        PageIter {
+            stream: Box::pin(stream),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Make a GET request at any relative endpoint and decode the response.
+    ///
+    /// See [`crate::Client::get_json`].
+    pub fn get_json<T: DeserializeOwned, S: AsRef<str>, P: Serialize + AsRef<str>>(
+        &self,
+        path: S,
+        query: &[(&str, P)],
+    ) -> Result<Response<T>, ClientError> {
+        self.runtime.block_on(self.inner.get_json(path, query))
+    }
+
+    /// Make a GET request at any relative endpoint without decoding the payload into a data model.
+    ///
+    /// See [`crate::Client::get_value`].
+    pub fn get_value<S: AsRef<str>, P: Serialize + AsRef<str>>(
+        &self,
+        path: S,
+        query: &[(&str, P)],
+    ) -> Result<Response<Value>, ClientError> {
+        self.runtime.block_on(self.inner.get_value(path, query))
+    }
+
+    /// Make a request with any HTTP method at any relative endpoint and decode the response.
+    ///
+    /// See [`crate::Client::request`].
+    pub fn request<
+        T: DeserializeOwned,
+        S: AsRef<str>,
+        P: Serialize + AsRef<str>,
+        B: Serialize,
+    >(
+        &self,
+        method: Method,
+        path: S,
+        query: &[(&str, P)],
+        body: Option<&B>,
+    ) -> Result<Response<T>, ClientError> {
+        self.runtime
+            .block_on(self.inner.request(method, path, query, body))
+    }
+
+    /// Get a snapshot of the client's request statistics.
+    ///
+    /// See [`crate::Client::stats`].
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    /// Get a copy of this client that bypasses the cache.
+    ///
+    /// See [`crate::Client::bypass_cache`].
+    pub fn bypass_cache(&self) -> Self {
+        Client {
+            inner: self.inner.bypass_cache(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get a copy of this client that requests different text formats.
+    ///
+    /// See [`crate::Client::with_text_format`].
+    pub fn with_text_format<I: IntoIterator<Item = TextFormat>>(
+        &self,
+        formats: I,
+    ) -> Self {
+        Client {
+            inner: self.inner.with_text_format(formats),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get the account info for the currently authed user.
+    ///
+    /// See [`crate::Client::account`].
+    pub fn account(&self) -> Result<Response<AccountResponse>, ClientError> {
+        self.runtime.block_on(self.inner.account())
+    }
+
+    /// Get an album.
+    ///
+    /// See [`crate::Client::album`].
+    pub fn album(&self, id: u32) -> Result<Response<AlbumResponse>, ClientError> {
+        self.runtime.block_on(self.inner.album(id))
+    }
+
+    /// Get an album's tracks.
+    ///
+    /// See [`crate::Client::album_tracks`].
+    pub fn album_tracks(
+        &self,
+        id: u32,
+        per_page: Option<u8>,
+        page: Option<u32>,
+    ) -> Result<Response<AlbumTracksResponse>, ClientError> {
+        self.runtime
+            .block_on(self.inner.album_tracks(id, per_page, page))
+    }
+
+    /// Iterate over every track on an album, fetching pages lazily.
+    ///
+    /// See [`crate::Client::album_tracks_stream`].
+    pub fn album_tracks_iter(
+        &self,
+        id: u32,
+        per_page: Option<u8>,
+        prefetch: bool,
+    ) -> impl Iterator<Item = Result<Track, ClientError>> {
+        self.iter(self.inner.album_tracks_stream(id, per_page, prefetch))
+    }
+
+    /// Get an annotation.
+    ///
+    /// See [`crate::Client::annotation`].
+    pub fn annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.runtime.block_on(self.inner.annotation(id))
+    }
+
+    /// Get annotations in a batch.
+    ///
+    /// See [`crate::Client::annotations`].
+    pub fn annotations<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> Vec<(u32, Result<Response<AnnotationResponse>, ClientError>)> {
+        self.runtime.block_on(self.inner.annotations(ids))
+    }
+
+    /// Iterate over annotations in a batch, in completion order.
+    ///
+    /// See [`crate::Client::annotations_stream`].
+    pub fn annotations_iter<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> impl Iterator<Item = (u32, Result<Response<AnnotationResponse>, ClientError>)>
+    {
+        self.iter(self.inner.annotations_stream(ids))
+    }
+
+    /// Create an annotation.
+    ///
+    /// See [`crate::Client::create_annotation`].
+    pub fn create_annotation(
+        &self,
+        annotation: &AnnotationPayload,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.runtime
+            .block_on(self.inner.create_annotation(annotation))
+    }
+
+    /// Update an annotation.
+    ///
+    /// See [`crate::Client::update_annotation`].
+    pub fn update_annotation(
+        &self,
+        id: u32,
+        annotation: &AnnotationPayload,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.runtime
+            .block_on(self.inner.update_annotation(id, annotation))
+    }
+
+    /// Delete an annotation.
+    ///
+    /// See [`crate::Client::delete_annotation`].
+    pub fn delete_annotation(&self, id: u32) -> Result<Response<()>, ClientError> {
+        self.runtime.block_on(self.inner.delete_annotation(id))
+    }
+
+    /// Upvote an annotation.
+    ///
+    /// See [`crate::Client::upvote_annotation`].
+    pub fn upvote_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.runtime.block_on(self.inner.upvote_annotation(id))
+    }
+
+    /// Downvote an annotation.
+    ///
+    /// See [`crate::Client::downvote_annotation`].
+    pub fn downvote_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.runtime.block_on(self.inner.downvote_annotation(id))
+    }
+
+    /// Remove a vote from an annotation.
+    ///
+    /// See [`crate::Client::unvote_annotation`].
+    pub fn unvote_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.runtime.block_on(self.inner.unvote_annotation(id))
+    }
+
+    /// Get an artist.
+    ///
+    /// See [`crate::Client::artist`].
+    pub fn artist(&self, id: u32) -> Result<Response<ArtistResponse>, ClientError> {
+        self.runtime.block_on(self.inner.artist(id))
+    }
+
+    /// Get artists in a batch.
+    ///
+    /// See [`crate::Client::artists`].
+    pub fn artists<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> Vec<(u32, Result<Response<ArtistResponse>, ClientError>)> {
+        self.runtime.block_on(self.inner.artists(ids))
+    }
+
+    /// Iterate over artists in a batch, in completion order.
+    ///
+    /// See [`crate::Client::artists_stream`].
+    pub fn artists_iter<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> impl Iterator<Item = (u32, Result<Response<ArtistResponse>, ClientError>)>
+    {
+        self.iter(self.inner.artists_stream(ids))
+    }
+
+    /// Get an artist's songs.
+    ///
+    /// See [`crate::Client::artist_songs`].
+    pub fn artist_songs(
+        &self,
+        id: u32,
+        sort: Option<ArtistSongsSort>,
+        per_page: Option<u8>,
+        page: Option<u32>,
+    ) -> Result<Response<ArtistSongsResponse>, ClientError> {
+        self.runtime
+            .block_on(self.inner.artist_songs(id, sort, per_page, page))
+    }
+
+    /// Iterate over every song by an artist, fetching pages lazily.
+    ///
+    /// See [`crate::Client::artist_songs_stream`].
+    pub fn artist_songs_iter(
+        &self,
+        id: u32,
+        sort: Option<ArtistSongsSort>,
+        per_page: Option<u8>,
+        prefetch: bool,
+    ) -> impl Iterator<Item = Result<SongCoreWithRDC<SongCoreStats>, ClientError>> {
+        self.iter(self.inner.artist_songs_stream(id, sort, per_page, prefetch))
+    }
+
+    /// Get a referent.
+    ///
+    /// See [`crate::Client::referent`].
+    pub fn referent(&self, id: u32) -> Result<Response<ReferentResponse>, ClientError> {
+        self.runtime.block_on(self.inner.referent(id))
+    }
+
+    /// Get referents.
+    ///
+    /// See [`crate::Client::referents`].
+    pub fn referents(
+        &self,
+        created_by: Option<u32>,
+        associated: Option<ReferentAssociation>,
+        per_page: Option<u8>,
+        page: Option<u32>,
+    ) -> Result<Response<ReferentsResponse>, ClientError> {
+        self.runtime
+            .block_on(self.inner.referents(created_by, associated, per_page, page))
+    }
+
+    /// Iterate over every referent, fetching pages lazily.
+    ///
+    /// See [`crate::Client::referents_stream`].
+    pub fn referents_iter(
+        &self,
+        created_by: Option<u32>,
+        associated: Option<ReferentAssociation>,
+        per_page: Option<u8>,
+        prefetch: bool,
+    ) -> impl Iterator<Item = Result<Referent, ClientError>> {
+        self.iter(
+            self.inner
+                .referents_stream(created_by, associated, per_page, prefetch),
+        )
+    }
+
+    /// Get search results.
+    ///
+    /// See [`crate::Client::search`].
+    pub fn search<S: AsRef<str>>(
+        &self,
+        query: S,
+    ) -> Result<Response<SearchResponse>, ClientError> {
+        self.runtime.block_on(self.inner.search(query))
+    }
+
+    /// Get a song.
+    ///
+    /// See [`crate::Client::song`].
+    pub fn song(&self, id: u32) -> Result<Response<SongResponse>, ClientError> {
+        self.runtime.block_on(self.inner.song(id))
+    }
+
+    /// Get songs in a batch.
+    ///
+    /// See [`crate::Client::songs`].
+    pub fn songs<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> Vec<(u32, Result<Response<SongResponse>, ClientError>)> {
+        self.runtime.block_on(self.inner.songs(ids))
+    }
+
+    /// Iterate over songs in a batch, in completion order.
+    ///
+    /// See [`crate::Client::songs_stream`].
+    pub fn songs_iter<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> impl Iterator<Item = (u32, Result<Response<SongResponse>, ClientError>)> {
+        self.iter(self.inner.songs_stream(ids))
+    }
+
+    /// Get a user.
+    ///
+    /// See [`crate::Client::user`].
+    pub fn user(&self, id: u32) -> Result<Response<UserResponse>, ClientError> {
+        self.runtime.block_on(self.inner.user(id))
+    }
+
+    /// Get a web page.
+    ///
+    /// See [`crate::Client::web_pages`].
+    pub fn web_pages(
+        &self,
+        raw_annotatable_url: Option<&str>,
+        canonical_url: Option<&str>,
+        og_url: Option<&str>,
+    ) -> Result<Response<WebPageResponse>, ClientError> {
+        self.runtime.block_on(self.inner.web_pages(
+            raw_annotatable_url,
+            canonical_url,
+            og_url,
+        ))
+    }
+}
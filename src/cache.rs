@@ -0,0 +1,454 @@
+//! Response caching.
+//!
+//! Cached responses are keyed by a hash of the client's auth tokens and the full endpoint URL
+//! with normalized (sorted) query parameters, which includes `text_format`.
+//! Clients with different tokens never share responses, even through a shared [`Cache`],
+//! so fields like `current_user_metadata` always belong to the client's own user.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs::{create_dir_all, read, remove_file, write},
+    io::Error as IoError,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::Endpoint;
+
+/// A cached response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// The final URL of the response.
+    pub url: String,
+    /// The HTTP status code.
+    pub status: u16,
+    /// The raw response body.
+    pub body: String,
+    /// When the response expires.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl CachedResponse {
+    /// Check if the response has expired.
+    ///
+    /// # Returns
+    ///
+    /// Whether the response is past its expiry.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Storage for cached responses.
+///
+/// Stores don't need to handle expiry, since expired responses are removed when they're looked up.
+pub trait CacheStore: Debug + Send + Sync {
+    /// Get a cached response.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    ///
+    /// # Returns
+    ///
+    /// The cached response, if there is one.
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    /// Store a response.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    /// * `response` - The response to store.
+    fn put(&self, key: &str, response: CachedResponse);
+
+    /// Remove a cached response.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    fn remove(&self, key: &str);
+}
+
+/// An in-memory store that evicts the least recently used response when full.
+#[derive(Debug)]
+pub struct MemoryStore {
+    /// maximum number of responses
+    capacity: usize,
+    /// store state
+    state: Mutex<MemoryState>,
+}
+
+/// The state of a [`MemoryStore`].
+#[derive(Debug, Default)]
+struct MemoryState {
+    /// responses by key, with their last use
+    entries: HashMap<String, (CachedResponse, u64)>,
+    /// keys by last use
+    uses: BTreeMap<u64, String>,
+    /// use counter
+    tick: u64,
+}
+
+impl MemoryState {
+    /// Mark a key as just used.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    /// * `last_use` - The key's previous use.
+    ///
+    /// # Returns
+    ///
+    /// The key's new use.
+    fn touch(&mut self, key: &str, last_use: Option<u64>) -> u64 {
+        if let Some(last_use) = last_use {
+            self.uses.remove(&last_use);
+        }
+        self.tick += 1;
+        self.uses.insert(self.tick, key.to_string());
+        self.tick
+    }
+}
+
+impl MemoryStore {
+    /// Create a new [`MemoryStore`].
+    ///
+    /// # Args
+    ///
+    /// * `capacity` - The maximum number of responses to keep.
+    ///
+    /// # Returns
+    ///
+    /// A new, empty [`MemoryStore`].
+    pub fn new(capacity: usize) -> Self {
+        MemoryStore {
+            capacity: capacity.max(1),
+            state: Mutex::new(MemoryState::default()),
+        }
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (response, last_use) = state.entries.get(key).cloned()?;
+        let tick = state.touch(key, Some(last_use));
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.1 = tick;
+        }
+        Some(response)
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let last_use = state.entries.get(key).map(|entry| entry.1);
+        if last_use.is_none() && state.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = state.uses.pop_first() {
+                state.entries.remove(&oldest);
+            }
+        }
+        let tick = state.touch(key, last_use);
+        state.entries.insert(key.to_string(), (response, tick));
+    }
+
+    fn remove(&self, key: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, last_use)) = state.entries.remove(key) {
+            state.uses.remove(&last_use);
+        }
+    }
+}
+
+/// A file system store that keeps one JSON file per response in a directory.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    /// directory for the cache files
+    directory: PathBuf,
+}
+
+/// A cache file.
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    /// The cache key, kept to guard against hash collisions.
+    key: String,
+    /// The cached response.
+    response: CachedResponse,
+}
+
+impl FileStore {
+    /// Create a new [`FileStore`].
+    ///
+    /// # Args
+    ///
+    /// * `directory` - The directory for the cache files; created if it doesn't exist.
+    ///
+    /// # Returns
+    ///
+    /// A new [`FileStore`].
+    /// [`std::io::Error`]s can occur if the directory can't be created.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Result<Self, IoError> {
+        let directory = directory.into();
+        create_dir_all(&directory)?;
+        Ok(FileStore { directory })
+    }
+
+    /// Get the path to the file for a key.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    ///
+    /// # Returns
+    ///
+    /// A path named after a stable hash of the key.
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory
+            .join(format!("{:016x}.json", stable_hash(key.bytes())))
+    }
+}
+
+impl CacheStore for FileStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let bytes = read(self.path(key)).ok()?;
+        let file = serde_json::from_slice::<CacheFile>(&bytes).ok()?;
+        (file.key == key).then_some(file.response)
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        let file = CacheFile {
+            key: key.to_string(),
+            response,
+        };
+        let result = serde_json::to_vec(&file)
+            .map_err(IoError::from)
+            .and_then(|bytes| write(self.path(key), bytes));
+        if let Err(e) = result {
+            warn!(target: "megamind::cache", "failed to write cache file: {}", e);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = remove_file(self.path(key));
+    }
+}
+
+/// A response cache configuration.
+///
+/// Only successful `GET` requests are cached, and cache hits skip both the network and the rate limiter.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    /// response storage
+    store: Arc<dyn CacheStore>,
+    /// time-to-live for endpoints without their own
+    default_ttl: Duration,
+    /// time-to-live by endpoint
+    ttls: HashMap<Endpoint, Duration>,
+}
+
+impl Cache {
+    /// Create a new [`Cache`].
+    ///
+    /// # Args
+    ///
+    /// * `store` - The response storage.
+    ///
+    /// # Returns
+    ///
+    /// A new [`Cache`] with a default time-to-live of one hour.
+    pub fn new<S: CacheStore + 'static>(store: S) -> Self {
+        Cache {
+            store: Arc::new(store),
+            default_ttl: Duration::from_secs(60 * 60),
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Set the default time-to-live.
+    ///
+    /// # Args
+    ///
+    /// * `ttl` - The time-to-live for endpoints without their own.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`Cache`].
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Set the time-to-live for an endpoint.
+    ///
+    /// A zero time-to-live disables caching for the endpoint.
+    ///
+    /// # Args
+    ///
+    /// * `endpoint` - The endpoint.
+    /// * `ttl` - The time-to-live for the endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`Cache`].
+    pub fn endpoint_ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// Get the time-to-live for an endpoint.
+    ///
+    /// # Args
+    ///
+    /// * `endpoint` - The endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The time-to-live, or [`None`] if the endpoint isn't cached.
+    pub(crate) fn ttl(&self, endpoint: Endpoint) -> Option<Duration> {
+        let ttl = self
+            .ttls
+            .get(&endpoint)
+            .copied()
+            .unwrap_or(self.default_ttl);
+        (!ttl.is_zero()).then_some(ttl)
+    }
+
+    /// Look up an unexpired response, removing it if it has expired.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    ///
+    /// # Returns
+    ///
+    /// The cached response, if there is an unexpired one.
+    pub(crate) fn get(&self, key: &str) -> Option<CachedResponse> {
+        let response = self.store.get(key)?;
+        if response.is_expired() {
+            self.store.remove(key);
+            None
+        } else {
+            Some(response)
+        }
+    }
+
+    /// Store a response.
+    ///
+    /// # Args
+    ///
+    /// * `key` - The cache key.
+    /// * `response` - The response to store.
+    pub(crate) fn put(&self, key: &str, response: CachedResponse) {
+        self.store.put(key, response);
+    }
+}
+
+/// Hash bytes with FNV-1a, which is stable across runs and compiler versions.
+///
+/// # Args
+///
+/// * `bytes` - The bytes to hash.
+///
+/// # Returns
+///
+/// The hash.
+pub(crate) fn stable_hash<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Build a cache key from a URL and its query parameters.
+///
+/// # Args
+///
+/// * `tokens` - A hash of the auth tokens the request is sent with.
+/// * `url` - The URL, without query parameters.
+/// * `query` - The query parameters.
+///
+/// # Returns
+///
+/// The token hash and the URL with its query parameters sorted and form-encoded.
+pub(crate) fn cache_key<P: AsRef<str>>(
+    tokens: u64,
+    url: &str,
+    query: &[(&str, P)],
+) -> String {
+    let mut pairs = query
+        .iter()
+        .map(|(k, v)| (*k, v.as_ref()))
+        .collect::<Vec<(&str, &str)>>();
+    pairs.sort();
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish();
+    format!("{:016x}:{}?{}", tokens, url, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str) -> CachedResponse {
+        CachedResponse {
+            url: String::from("https://api.genius.com/songs/1"),
+            status: 200,
+            body: body.to_string(),
+            expires_at: Utc::now() + chrono::Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn test_memory_store_evicts_least_recently_used() {
+        let store = MemoryStore::new(2);
+        store.put("a", response("a"));
+        store.put("b", response("b"));
+        assert!(store.get("a").is_some());
+        store.put("c", response("c"));
+        assert!(store.get("a").is_some());
+        assert!(store.get("b").is_none());
+        assert!(store.get("c").is_some());
+    }
+
+    #[test]
+    fn test_cache_key_normalized() {
+        assert_eq!(
+            cache_key(1, "/search", &[("q", "x"), ("text_format", "plain")]),
+            cache_key(1, "/search", &[("text_format", "plain"), ("q", "x")])
+        );
+        assert_ne!(
+            cache_key(1, "/search", &[("q", "x")]),
+            cache_key(2, "/search", &[("q", "x")])
+        );
+        assert_ne!(
+            cache_key(1, "/search", &[("q", "a&b=c")]),
+            cache_key(1, "/search", &[("q", "a"), ("b", "c")])
+        );
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let directory = std::env::temp_dir()
+            .join(format!("megamind-file-store-{}", std::process::id()));
+        let store = FileStore::new(&directory).unwrap();
+        let cached = response("a");
+        store.put("a", cached.clone());
+        assert_eq!(store.get("a"), Some(cached));
+        assert!(store.get("b").is_none());
+        let cache = Cache::new(store.clone());
+        let mut expired = response("expired");
+        expired.expires_at = Utc::now() - chrono::Duration::seconds(1);
+        cache.put("b", expired);
+        assert!(store.get("b").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(!store.path("b").exists());
+        store.remove("a");
+        assert!(store.get("a").is_none());
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
@@ -0,0 +1,325 @@
+//! Record and replay cassettes for offline testing.
+//!
+//! In record mode, every request that reaches the network is saved to a cassette file,
+//! along with its response. In replay mode, responses are served from the cassette file
+//! without any network access, which makes tests of code built on this library deterministic.
+use std::{
+    collections::BTreeMap,
+    fs::{read, write},
+    io::Error as IoError,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Error as JsonError, Value};
+use thiserror::Error;
+
+/// A recorded request and response pair.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The HTTP method.
+    pub method: String,
+    /// The full URL, without query parameters.
+    pub url: String,
+    /// The query parameters, sorted.
+    pub query: Vec<(String, String)>,
+    /// The JSON request body, if there was one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<Value>,
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The response headers.
+    pub headers: BTreeMap<String, String>,
+    /// The raw response body.
+    pub body: String,
+}
+
+impl Interaction {
+    /// Check if the interaction matches a request.
+    ///
+    /// # Args
+    ///
+    /// * `method` - The HTTP method.
+    /// * `url` - The full URL, without query parameters.
+    /// * `query` - The query parameters, sorted.
+    /// * `body` - The JSON request body, if there is one.
+    ///
+    /// # Returns
+    ///
+    /// Whether the method, URL, query parameters, and request body all match.
+    fn matches(
+        &self,
+        method: &str,
+        url: &str,
+        query: &[(String, String)],
+        body: Option<&Value>,
+    ) -> bool {
+        self.method == method
+            && self.url == url
+            && self.query == query
+            && self.request_body.as_ref() == body
+    }
+}
+
+/// The contents of a cassette file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Tape {
+    /// Recorded interactions, in order.
+    interactions: Vec<Interaction>,
+}
+
+/// Whether a cassette is recording or replaying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests and save them to the cassette file.
+    Record,
+    /// Serve responses from the cassette file without sending requests.
+    Replay,
+}
+
+/// Errors that can occur while recording or replaying a cassette.
+#[derive(Debug, Error)]
+pub enum CassetteError {
+    /// The cassette file couldn't be read or written.
+    #[error("cassette file error: {0}")]
+    Io(#[from] IoError),
+    /// The cassette file isn't valid.
+    #[error("cassette format error: {0}")]
+    Format(#[from] JsonError),
+    /// No recorded interaction matches the request.
+    #[error("no recorded interaction for {method} {url} with query {query:?} and body {body:?}")]
+    Unmatched {
+        /// The HTTP method.
+        method: String,
+        /// The full URL, without query parameters.
+        url: String,
+        /// The query parameters, sorted.
+        query: Vec<(String, String)>,
+        /// The JSON request body, if there was one.
+        body: Option<Value>,
+    },
+    /// A recorded interaction can't be turned back into a response.
+    #[error("invalid recorded interaction: {0}")]
+    Invalid(String),
+}
+
+/// A cassette, shared between clones.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    /// path to the cassette file
+    path: PathBuf,
+    /// recording or replaying
+    mode: CassetteMode,
+    /// shared cassette state
+    state: Arc<Mutex<CassetteState>>,
+}
+
+/// The state of a cassette.
+#[derive(Debug, Default)]
+struct CassetteState {
+    /// recorded interactions
+    tape: Tape,
+    /// number of times each interaction has been replayed
+    plays: Vec<usize>,
+}
+
+impl Cassette {
+    /// Create a cassette that records to a file.
+    ///
+    /// The file is overwritten after every recorded interaction.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path to the cassette file.
+    ///
+    /// # Returns
+    ///
+    /// A new, empty [`Cassette`] in record mode.
+    pub fn record<P: Into<PathBuf>>(path: P) -> Self {
+        Cassette {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            state: Arc::new(Mutex::new(CassetteState::default())),
+        }
+    }
+
+    /// Create a cassette that replays from a file.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path to the cassette file.
+    ///
+    /// # Returns
+    ///
+    /// A [`Cassette`] in replay mode.
+    /// [`CassetteError`]s can occur if the file can't be read or isn't a valid cassette.
+    pub fn replay<P: Into<PathBuf>>(path: P) -> Result<Self, CassetteError> {
+        let path = path.into();
+        let tape = serde_json::from_slice::<Tape>(&read(&path)?)?;
+        let plays = vec![0; tape.interactions.len()];
+        Ok(Cassette {
+            path,
+            mode: CassetteMode::Replay,
+            state: Arc::new(Mutex::new(CassetteState { tape, plays })),
+        })
+    }
+
+    /// Get the cassette mode.
+    ///
+    /// # Returns
+    ///
+    /// Whether the cassette is recording or replaying.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Get the recorded interactions.
+    ///
+    /// # Returns
+    ///
+    /// A copy of every recorded interaction, in order.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().tape.interactions.clone()
+    }
+
+    /// Lock the cassette state.
+    fn lock(&self) -> std::sync::MutexGuard<'_, CassetteState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Find a recorded interaction for a request.
+    ///
+    /// Identical requests are served their recorded responses in order,
+    /// and the last one is repeated once every response has been played.
+    ///
+    /// # Args
+    ///
+    /// * `method` - The HTTP method.
+    /// * `url` - The full URL, without query parameters.
+    /// * `query` - The query parameters, sorted.
+    /// * `body` - The JSON request body, if there is one.
+    ///
+    /// # Returns
+    ///
+    /// The matching interaction.
+    /// [`CassetteError::Unmatched`] occurs if nothing matches.
+    pub(crate) fn play(
+        &self,
+        method: &str,
+        url: &str,
+        query: Vec<(String, String)>,
+        body: Option<Value>,
+    ) -> Result<Interaction, CassetteError> {
+        let mut state = self.lock();
+        let matches = state
+            .tape
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.matches(method, url, &query, body.as_ref()))
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+        let index = matches
+            .iter()
+            .find(|&&index| state.plays[index] == 0)
+            .or(matches.last())
+            .copied()
+            .ok_or_else(|| CassetteError::Unmatched {
+                method: method.to_string(),
+                url: url.to_string(),
+                query,
+                body,
+            })?;
+        state.plays[index] += 1;
+        Ok(state.tape.interactions[index].clone())
+    }
+
+    /// Record an interaction and save the cassette file.
+    ///
+    /// # Args
+    ///
+    /// * `interaction` - The interaction to record.
+    ///
+    /// # Returns
+    ///
+    /// Nothing, or a [`CassetteError`] if the file couldn't be written.
+    pub(crate) fn record_interaction(
+        &self,
+        interaction: Interaction,
+    ) -> Result<(), CassetteError> {
+        let mut state = self.lock();
+        state.tape.interactions.push(interaction);
+        state.plays.push(0);
+        write(&self.path, serde_json::to_vec_pretty(&state.tape)?)?;
+        Ok(())
+    }
+}
+
+/// Normalize query parameters for recording and matching.
+///
+/// # Args
+///
+/// * `query` - The query parameters.
+///
+/// # Returns
+///
+/// The query parameters as owned pairs, sorted.
+pub(crate) fn normalize_query<P: AsRef<str>>(
+    query: &[(&str, P)],
+) -> Vec<(String, String)> {
+    let mut pairs = query
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.as_ref().to_string()))
+        .collect::<Vec<(String, String)>>();
+    pairs.sort();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, process::id};
+
+    use super::*;
+
+    fn interaction(body: &str) -> Interaction {
+        Interaction {
+            method: String::from("GET"),
+            url: String::from("https://api.genius.com/songs/1"),
+            query: vec![(String::from("text_format"), String::from("html,plain"))],
+            request_body: None,
+            status: 200,
+            headers: BTreeMap::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = temp_dir().join(format!("megamind-cassette-{}.json", id()));
+        let recorder = Cassette::record(&path);
+        recorder.record_interaction(interaction("first")).unwrap();
+        recorder.record_interaction(interaction("second")).unwrap();
+
+        let player = Cassette::replay(&path).unwrap();
+        let request = interaction("");
+        let play = || player.play("GET", &request.url, request.query.clone(), None);
+        assert_eq!(play().unwrap().body, "first");
+        assert_eq!(play().unwrap().body, "second");
+        assert_eq!(play().unwrap().body, "second");
+        assert!(matches!(
+            player.play("GET", &request.url, Vec::new(), None),
+            Err(CassetteError::Unmatched { .. })
+        ));
+        assert!(matches!(
+            player.play(
+                "GET",
+                &request.url,
+                request.query.clone(),
+                Some(Value::Bool(true))
+            ),
+            Err(CassetteError::Unmatched { .. })
+        ));
+        let _ = std::fs::remove_file(path);
+    }
+}
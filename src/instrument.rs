@@ -0,0 +1,117 @@
+//! Request instrumentation for logging and the optional `tracing` feature.
+#[cfg(feature = "tracing")]
+use reqwest::Method;
+
+#[cfg(feature = "tracing")]
+use crate::Endpoint;
+
+/// Query parameters whose values are never logged or traced.
+const SENSITIVE_QUERY_PARAMS: [&str; 4] =
+    ["access_token", "client_secret", "code", "token"];
+
+/// Format query parameters for logging, with sensitive values redacted.
+///
+/// # Args
+///
+/// * `query` - The query parameters.
+///
+/// # Returns
+///
+/// The comma-separated `key=value` pairs.
+pub(crate) fn redact_query<P: AsRef<str>>(query: &[(&str, P)]) -> String {
+    query
+        .iter()
+        .map(|(k, v)| {
+            if SENSITIVE_QUERY_PARAMS.contains(k) {
+                format!("{}=[REDACTED]", k)
+            } else {
+                format!("{}={}", k, v.as_ref())
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Find the Genius ID in an endpoint path.
+///
+/// # Args
+///
+/// * `path` - The relative endpoint path.
+///
+/// # Returns
+///
+/// The first numeric path segment (e.g., `1` in `/annotations/1/upvote`), if there is one.
+#[cfg(any(feature = "tracing", test))]
+pub(crate) fn genius_id(path: &str) -> Option<u32> {
+    path.split('/').find_map(|segment| segment.parse().ok())
+}
+
+/// Open a span for an API call.
+///
+/// The `status`, `retries`, `cache`, `response_size`, `latency_ms`, and `token` fields start out empty
+/// and are recorded with [`record!`] as the call progresses.
+///
+/// # Args
+///
+/// * `endpoint` - The endpoint being requested.
+/// * `method` - The HTTP method.
+/// * `path` - The relative endpoint path.
+/// * `query` - The query parameters, already redacted.
+///
+/// # Returns
+///
+/// A new span.
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(
+    endpoint: Endpoint,
+    method: &Method,
+    path: &str,
+    query: &str,
+) -> tracing::Span {
+    use tracing::field::Empty;
+    tracing::info_span!(
+        target: "megamind::request",
+        "megamind.request",
+        endpoint = endpoint.name(),
+        method = %method,
+        path = path,
+        genius_id = genius_id(path),
+        query = query,
+        status = Empty,
+        retries = Empty,
+        cache = Empty,
+        response_size = Empty,
+        latency_ms = Empty,
+        token = Empty,
+    )
+}
+
+/// Record a field on the current request span, if the `tracing` feature is enabled.
+macro_rules! record {
+    ($field:literal, $value:expr) => {{
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, $value);
+        #[cfg(not(feature = "tracing"))]
+        let _ = &$value;
+    }};
+}
+pub(crate) use record;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_query() {
+        assert_eq!(
+            redact_query(&[("q", "kendrick"), ("access_token", "secret")]),
+            "q=kendrick,access_token=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_genius_id() {
+        assert_eq!(genius_id("/annotations/10/upvote"), Some(10));
+        assert_eq!(genius_id("/web_pages/lookup"), None);
+    }
+}
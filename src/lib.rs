@@ -11,16 +11,53 @@
 )]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
-use log::info;
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter, Result as FmtResult},
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
+use log::{debug, info, warn};
 use reqwest::{
-    header::{HeaderMap, HeaderValue, InvalidHeaderValue, AUTHORIZATION},
-    Client as ReqwestClient, Error as ReqwestError, StatusCode,
+    header::{HeaderMap, HeaderName, HeaderValue, InvalidHeaderValue, AUTHORIZATION},
+    Client as ReqwestClient, Error as ReqwestError, StatusCode, Url,
 };
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use thiserror::Error;
+use tokio::time::sleep;
 
+mod batch;
+use batch::{batch_ordered, batch_unordered, DEFAULT_CONCURRENCY};
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+use cache::{cache_key, Cache, CachedResponse};
+pub mod cassette;
+mod instrument;
+use cassette::{normalize_query, Cassette, CassetteError, CassetteMode, Interaction};
+use instrument::{record, redact_query};
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod models;
 use models::*;
+pub mod oauth;
+use oauth::{Scope, Scopes};
+mod pagination;
+use pagination::{paginate, Page};
+pub mod rate_limit;
+use rate_limit::{RateLimit, RateLimiter};
+pub mod retry;
+use retry::{is_transient, retry_after, RetryPolicy};
+pub mod stats;
+use stats::{ResponseSource, Stats, StatsCollector};
+pub mod token_pool;
+use token_pool::{TokenPool, TokenRotation};
+
+pub use reqwest::{Method, Proxy};
 
 /// The base URL for the API.
 pub const BASE_URL: &str = "https://api.genius.com";
@@ -33,14 +70,307 @@ pub enum ClientError {
     General(#[from] ReqwestError),
     /// A rate limit error.
     #[error("megamind rate limit error")]
-    RateLimited,
+    RateLimited {
+        /// The delay requested by the `Retry-After` header, if any.
+        retry_after: Option<Duration>,
+    },
+    /// An error returned by the API.
+    #[error("megamind API error: {0}")]
+    Api(#[from] ApiError),
+    /// The client's known scopes don't include the scope required by the endpoint.
+    #[error("megamind missing scope: {0}")]
+    MissingScope(Scope),
+    /// A response body that doesn't match the data models.
+    #[error("megamind decode error: {0}")]
+    Decode(Box<DecodeError>),
+    /// A cassette couldn't be recorded to or replayed from.
+    #[error("megamind cassette error: {0}")]
+    Cassette(#[from] CassetteError),
+}
+
+/// Folds API errors into [`ClientError`]s.
+///
+/// Implemented for the results of the [`Client`] endpoint methods, so that
+/// `client.song(id).await.into_api_result()?` returns the response payload
+/// and treats cases like a nonexistent Genius ID as an ordinary [`Err`].
+pub trait ApiResult<T> {
+    /// Fold any [`Response::Error`] or [`Response::Other`] into a [`ClientError::Api`].
+    ///
+    /// # Returns
+    ///
+    /// The response payload, or a [`ClientError`].
+    fn into_api_result(self) -> Result<T, ClientError>;
+}
+
+impl<T> ApiResult<T> for Result<Response<T>, ClientError> {
+    fn into_api_result(self) -> Result<T, ClientError> {
+        Ok(self?.into_result()?)
+    }
+}
+
+/// The maximum number of characters kept in [`DecodeError::excerpt`].
+const EXCERPT_LENGTH: usize = 512;
+
+/// Details about a response body that couldn't be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+    /// The endpoint URL, including query parameters.
+    pub url: Url,
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+    /// The JSON path to the failing field (e.g., `response.song.media[2].provider`).
+    pub path: String,
+    /// The error message from Serde.
+    pub message: String,
+    /// The start of the raw response body, truncated to a reasonable length.
+    pub excerpt: String,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} at `{}` ({} from {}), body: {}",
+            self.message, self.path, self.status, self.url, self.excerpt
+        )
+    }
+}
+
+impl DecodeError {
+    /// Create a new [`DecodeError`].
+    ///
+    /// # Args
+    ///
+    /// * `url` - The endpoint URL.
+    /// * `status` - The HTTP status.
+    /// * `path` - The JSON path to the failing field.
+    /// * `message` - The error message.
+    /// * `body` - The raw response body.
+    ///
+    /// # Returns
+    ///
+    /// A new [`DecodeError`] with a truncated excerpt of the body.
+    fn new(
+        url: Url,
+        status: StatusCode,
+        path: String,
+        message: String,
+        body: &[u8],
+    ) -> Self {
+        let body = String::from_utf8_lossy(body);
+        let mut excerpt: String = body.chars().take(EXCERPT_LENGTH).collect();
+        if excerpt.len() < body.len() {
+            excerpt.push_str("...");
+        }
+        DecodeError {
+            url,
+            status,
+            path,
+            message,
+            excerpt,
+        }
+    }
+}
+
+/// A raw response, before decoding.
+#[derive(Debug)]
+struct RawResponse {
+    /// The final URL of the response.
+    url: Url,
+    /// The HTTP status.
+    status: StatusCode,
+    /// The response headers.
+    headers: HeaderMap,
+    /// The raw response body.
+    body: Vec<u8>,
+    /// Where the response came from.
+    source: ResponseSource,
+}
+
+impl RawResponse {
+    /// Create a [`RawResponse`] from a cached response.
+    ///
+    /// # Args
+    ///
+    /// * `cached` - The cached response.
+    ///
+    /// # Returns
+    ///
+    /// The raw response, or [`None`] if the cached response is invalid.
+    fn from_cached(cached: CachedResponse) -> Option<Self> {
+        Some(RawResponse {
+            url: Url::parse(&cached.url).ok()?,
+            status: StatusCode::from_u16(cached.status).ok()?,
+            headers: HeaderMap::new(),
+            body: cached.body.into_bytes(),
+            source: ResponseSource::Cache,
+        })
+    }
+
+    /// Create a [`RawResponse`] from a recorded interaction.
+    ///
+    /// # Args
+    ///
+    /// * `interaction` - The recorded interaction.
+    ///
+    /// # Returns
+    ///
+    /// The raw response.
+    /// [`CassetteError::Invalid`] occurs if the URL, status, or headers are invalid.
+    fn from_interaction(interaction: Interaction) -> Result<Self, CassetteError> {
+        let invalid = |e: &dyn Display| CassetteError::Invalid(e.to_string());
+        let url = Url::parse_with_params(&interaction.url, &interaction.query)
+            .map_err(|e| invalid(&e))?;
+        let status =
+            StatusCode::from_u16(interaction.status).map_err(|e| invalid(&e))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &interaction.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e))?,
+                HeaderValue::from_str(value).map_err(|e| invalid(&e))?,
+            );
+        }
+        Ok(RawResponse {
+            url,
+            status,
+            headers,
+            body: interaction.body.into_bytes(),
+            source: ResponseSource::Cassette,
+        })
+    }
+
+    /// Create a recorded interaction.
+    ///
+    /// # Args
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `url` - The full URL of the request, without query parameters.
+    /// * `query` - The normalized query parameters of the request.
+    /// * `request_body` - The JSON body of the request, if there was one.
+    ///
+    /// # Returns
+    ///
+    /// The recorded interaction; headers that aren't valid UTF-8 are skipped.
+    fn to_interaction(
+        &self,
+        method: &Method,
+        url: &str,
+        query: Vec<(String, String)>,
+        request_body: Option<Value>,
+    ) -> Interaction {
+        Interaction {
+            method: method.to_string(),
+            url: url.to_string(),
+            query,
+            request_body,
+            status: self.status.as_u16(),
+            headers: self
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: String::from_utf8_lossy(&self.body).into_owned(),
+        }
+    }
+
+    /// Create a cached response.
+    ///
+    /// # Args
+    ///
+    /// * `ttl` - The time-to-live of the cached response.
+    ///
+    /// # Returns
+    ///
+    /// The cached response, or [`None`] if the body isn't valid UTF-8.
+    /// A time-to-live too long to represent (e.g., [`Duration::MAX`]) never expires.
+    fn to_cached(&self, ttl: Duration) -> Option<CachedResponse> {
+        Some(CachedResponse {
+            url: self.url.to_string(),
+            status: self.status.as_u16(),
+            body: String::from_utf8(self.body.clone()).ok()?,
+            expires_at: chrono::Duration::from_std(ttl)
+                .ok()
+                .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        })
+    }
+}
+
+/// Record the outcome of a request on the current request span.
+///
+/// # Args
+///
+/// * `raw` - The raw response.
+/// * `start` - When the request started.
+fn record_response(raw: &RawResponse, start: Instant) {
+    record!("status", raw.status.as_u16());
+    record!("response_size", raw.body.len() as u64);
+    record!("latency_ms", start.elapsed().as_millis() as u64);
+}
+
+/// A successful response envelope.
+///
+/// Decoded on its own because [`Response`] is untagged, which hides the path to any failing field.
+#[derive(Deserialize)]
+struct SuccessEnvelope<T> {
+    /// The response metadata.
+    meta: SuccessMeta,
+    /// The response payload.
+    response: T,
+}
+
+/// Decode a response body.
+///
+/// # Args
+///
+/// * `url` - The endpoint URL.
+/// * `status` - The HTTP status.
+/// * `body` - The raw response body.
+///
+/// # Returns
+///
+/// A [`Response`], or a [`ClientError::Decode`] pointing at the failing field.
+fn decode<T: DeserializeOwned>(
+    url: Url,
+    status: StatusCode,
+    body: &[u8],
+) -> Result<Response<T>, ClientError> {
+    let error = |path: String, message: String| {
+        ClientError::Decode(Box::new(DecodeError::new(
+            url.clone(),
+            status,
+            path,
+            message,
+            body,
+        )))
+    };
+    let value = serde_json::from_slice::<Value>(body)
+        .map_err(|e| error(String::from("."), e.to_string()))?;
+    let is_success = value
+        .pointer("/meta/status")
+        .and_then(Value::as_u64)
+        .is_some_and(|status| status < 400)
+        && value.get("response").is_some();
+    if is_success {
+        serde_path_to_error::deserialize::<_, SuccessEnvelope<T>>(value)
+            .map(|envelope| Response::Success {
+                meta: envelope.meta,
+                response: envelope.response,
+            })
+            .map_err(|e| error(e.path().to_string(), e.inner().to_string()))
+    } else {
+        serde_path_to_error::deserialize::<_, Response<T>>(value)
+            .map_err(|e| error(e.path().to_string(), e.inner().to_string()))
+    }
 }
 
 /// An HTTP client for interacting with the Genius API.
 ///
 /// Essentially just a thin wrapper around [`reqwest::Client`],
-/// meaning that if you want more control/need to access a missing endpoint
-/// then you can just use the data models with Reqwest directly.
+/// meaning that if you want more control then you can just use the data models with Reqwest directly.
+/// Missing endpoints can be reached with [`Client::get_json`], [`Client::get_value`], and [`Client::request`].
 ///
 /// This also means that you can clone this client freely
 /// and **should not** use [`std::sync::Arc`] or [`std::rc::Rc`], much like [`reqwest::Client`].
@@ -48,274 +378,1854 @@ pub enum ClientError {
 pub struct Client {
     // internal Reqwest client
     internal: ReqwestClient,
+    // auth tokens shared between clones
+    tokens: TokenPool,
+    // base URL for every endpoint
+    base_url: String,
+    // retry policy for every endpoint
+    retry: RetryPolicy,
+    // rate limiter shared between clones
+    limiter: Option<RateLimiter>,
+    // scopes granted to the auth token, if known
+    scopes: Option<Scopes>,
+    // response cache shared between clones
+    cache: Option<Cache>,
+    // whether to skip cache lookups
+    cache_bypass: bool,
+    // cassette to record to or replay from
+    cassette: Option<Cassette>,
+    // `text_format` query value for every endpoint
+    text_format: String,
+    // maximum number of requests in flight for batch methods
+    concurrency: usize,
+    // statistics shared between clones
+    stats: StatsCollector,
 }
 
 impl Client {
-    /// Make a generic GET request at a specified relative endpoint.
+    /// Send a request at a specified relative endpoint.
     ///
     /// # Args
     ///
-    /// * `endpoint` - The relative endpoint; should have "/" prepended.
+    /// * `endpoint` - The endpoint being requested.
+    /// * `method` - The HTTP method.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
     /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
+    /// * `body` - An optional JSON body.
     ///
     /// # Returns
     ///
-    /// A [`Response`].
-    /// [`reqwest::Error`]s can occur if the request fails at the [`reqwest`] level, which includes HTTP related things and JSON parsing.
-    async fn get<T: DeserializeOwned, S: AsRef<str>, P: Serialize + AsRef<str>>(
+    /// The raw response.
+    /// [`reqwest::Error`]s can occur if the request fails at the [`reqwest`] level.
+    /// Rate limits, server errors, and transient connection failures are retried according to the client's [`RetryPolicy`],
+    /// although `POST` requests are only retried if they were rate limited or never connected.
+    /// Every attempt waits for the client's [`RateLimit`], if one is configured.
+    /// Successful `GET` requests are served from and stored in the client's [`Cache`], if one is configured.
+    /// Requests that miss the cache are recorded to or replayed from the client's [`Cassette`], if one is configured.
+    /// [`ClientError::MissingScope`] occurs before sending if the client's known scopes don't cover the endpoint.
+    /// With the `tracing` feature, each call runs in a `megamind.request` span.
+    async fn send<S: AsRef<str>, P: Serialize + AsRef<str>, B: Serialize>(
         &self,
-        endpoint: S,
+        endpoint: Endpoint,
+        method: Method,
+        path: S,
         query: &[(&str, P)],
-    ) -> Result<Response<T>, ClientError> {
-        info!(
-            target: "megamind::get",
-            "endpoint: \"{}\", queries: \"{}\"",
-            endpoint.as_ref(),
-            query
-                .iter()
-                .map(|q| format!("{}={}", q.0, q.1.as_ref()))
-                .collect::<Vec<String>>()
-                .join(",")
-        );
-        let response = self
-            .internal
-            .get(format!("{}{}", BASE_URL, endpoint.as_ref()))
-            .query(query)
-            .send()
-            .await?;
-        let resp_url = response.url().clone();
-        if response.status() == StatusCode::TOO_MANY_REQUESTS {
-            return Err(ClientError::RateLimited);
-        }
-        Ok(response
-            .json::<Response<T>>()
-            .await
-            .map_err(|e| e.with_url(resp_url))?)
+        body: Option<&B>,
+    ) -> Result<RawResponse, ClientError> {
+        let redacted = redact_query(query);
+        #[cfg(feature = "tracing")]
+        let span =
+            instrument::request_span(endpoint, &method, path.as_ref(), &redacted);
+        let future =
+            self.send_inner(endpoint, method, path.as_ref(), query, &redacted, body);
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, span);
+        future.await
     }
 
-    /// Get the account info for the currently authed user.
+    /// Send a request at a specified relative endpoint, within its span.
     ///
-    /// Requires scope: `me`.
+    /// # Args
+    ///
+    /// * `endpoint` - The endpoint being requested.
+    /// * `method` - The HTTP method.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
+    /// * `redacted` - The query parameters, formatted for logging.
+    /// * `body` - An optional JSON body.
     ///
     /// # Returns
     ///
-    /// The current user.
-    pub async fn account(&self) -> Result<Response<AccountResponse>, ClientError> {
-        self.get("/account", &[("text_format", "html,plain")]).await
+    /// The raw response.
+    async fn send_inner<P: Serialize + AsRef<str>, B: Serialize>(
+        &self,
+        endpoint: Endpoint,
+        method: Method,
+        path: &str,
+        query: &[(&str, P)],
+        redacted: &str,
+        body: Option<&B>,
+    ) -> Result<RawResponse, ClientError> {
+        if let (Some(scopes), Some(scope)) = (&self.scopes, endpoint.required_scope()) {
+            if !scopes.contains(scope) {
+                return Err(ClientError::MissingScope(scope));
+            }
+        }
+        info!(
+            target: "megamind::get",
+            "method: \"{}\", endpoint: \"{}\", queries: \"{}\"",
+            method,
+            path,
+            redacted
+        );
+        let start = Instant::now();
+        let url = format!("{}{}", self.base_url, path);
+        let cache = match (&self.cache, method == Method::GET) {
+            (Some(cache), true) => cache
+                .ttl(endpoint)
+                .map(|ttl| (cache, cache_key(self.tokens.hash(), &url, query), ttl)),
+            _ => None,
+        };
+        if let Some((cache, key, _)) = &cache {
+            if let Some(raw) = cache
+                .get(key)
+                .filter(|_| !self.cache_bypass)
+                .and_then(RawResponse::from_cached)
+            {
+                debug!(target: "megamind::cache", "cache hit: \"{}\"", key);
+                record!("cache", "hit");
+                record_response(&raw, start);
+                return Ok(raw);
+            }
+            record!("cache", "miss");
+        }
+        let raw = match &self.cassette {
+            Some(cassette) => {
+                self.send_with_cassette(cassette, method, &url, query, body)
+                    .await?
+            }
+            None => self.send_with_retry(method, &url, query, body).await?,
+        };
+        record_response(&raw, start);
+        if raw.status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(ClientError::RateLimited {
+                retry_after: retry_after(&raw.headers),
+            });
+        }
+        if let (Some((cache, key, ttl)), true) = (cache, raw.status.is_success()) {
+            if let Some(cached) = raw.to_cached(ttl) {
+                cache.put(&key, cached);
+            }
+        }
+        Ok(raw)
     }
 
-    ///  Get an annotation.
+    /// Send a request through a cassette.
     ///
     /// # Args
     ///
-    /// * `id` - A Genius ID.
+    /// * `cassette` - The cassette to record to or replay from.
+    /// * `method` - The HTTP method.
+    /// * `url` - The full URL.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
+    /// * `body` - An optional JSON body.
     ///
     /// # Returns
     ///
-    /// The annotation associated with the ID.
-    pub async fn annotation(
+    /// The raw response.
+    /// In record mode, only the final response after any retries is recorded, including a final `429`.
+    /// In replay mode, nothing is sent and [`CassetteError::Unmatched`] occurs if no recorded interaction matches.
+    async fn send_with_cassette<P: Serialize + AsRef<str>, B: Serialize>(
         &self,
-        id: u32,
-    ) -> Result<Response<AnnotationResponse>, ClientError> {
-        self.get(
-            format!("/annotations/{}", id),
-            &[("text_format", "html,plain")],
-        )
-        .await
+        cassette: &Cassette,
+        method: Method,
+        url: &str,
+        query: &[(&str, P)],
+        body: Option<&B>,
+    ) -> Result<RawResponse, ClientError> {
+        let normalized = normalize_query(query);
+        let request_body = body
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(CassetteError::from)?;
+        match cassette.mode() {
+            CassetteMode::Replay => {
+                debug!(target: "megamind::cassette", "replaying \"{} {}\"", method, url);
+                let interaction =
+                    cassette.play(method.as_str(), url, normalized, request_body)?;
+                Ok(RawResponse::from_interaction(interaction)?)
+            }
+            CassetteMode::Record => {
+                let raw = self
+                    .send_with_retry(method.clone(), url, query, body)
+                    .await?;
+                debug!(target: "megamind::cassette", "recording \"{} {}\"", method, url);
+                cassette.record_interaction(raw.to_interaction(
+                    &method,
+                    url,
+                    normalized,
+                    request_body,
+                ))?;
+                Ok(raw)
+            }
+        }
     }
 
-    /// Get an artist.
+    /// Send a request, retrying according to the client's [`RetryPolicy`].
     ///
     /// # Args
     ///
-    /// * `id` - A Genius ID.
+    /// * `method` - The HTTP method.
+    /// * `url` - The full URL.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
+    /// * `body` - An optional JSON body.
     ///
     /// # Returns
     ///
-    /// The artist associated with the ID.
-    pub async fn artist(
+    /// The raw response, which is a `429` if the client ran out of retries while rate limited.
+    async fn send_with_retry<P: Serialize + AsRef<str>, B: Serialize>(
         &self,
-        id: u32,
-    ) -> Result<Response<ArtistResponse>, ClientError> {
-        self.get(format!("/artists/{}", id), &[("text_format", "html,plain")])
+        method: Method,
+        url: &str,
+        query: &[(&str, P)],
+        body: Option<&B>,
+    ) -> Result<RawResponse, ClientError> {
+        let idempotent = method != Method::POST;
+        let mut attempt = 1;
+        let mut failed_over = false;
+        let response = loop {
+            if let Some(limiter) = &self.limiter {
+                limiter.acquire().await;
+            }
+            let (token, auth_header) = self.tokens.select(Instant::now());
+            debug!(target: "megamind::request", "sending \"{}\" with token {}", url, token);
+            record!("token", token);
+            let mut request = self
+                .internal
+                .request(method.clone(), url)
+                .header(AUTHORIZATION, auth_header)
+                .query(query);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let result = request.send().await;
+            if let Ok(response) = &result {
+                let status = response.status();
+                self.stats.record_token(token, status);
+                if status == StatusCode::TOO_MANY_REQUESTS
+                    || status == StatusCode::UNAUTHORIZED
+                {
+                    self.tokens.bench(
+                        token,
+                        Instant::now(),
+                        retry_after(response.headers()),
+                    );
+                    if !failed_over && self.tokens.can_fail_over(Instant::now()) {
+                        failed_over = true;
+                        warn!(
+                            target: "megamind::request",
+                            "token {} got {} for \"{}\", trying another token",
+                            token,
+                            status,
+                            url
+                        );
+                        continue;
+                    }
+                }
+            }
+            let delay = match result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    let retry_after = retry_after(response.headers());
+                    match self.retry.delay(attempt, retry_after) {
+                        Some(delay) => delay,
+                        None => break response,
+                    }
+                }
+                Ok(response) if idempotent && response.status().is_server_error() => {
+                    match self.retry.delay(attempt, None) {
+                        Some(delay) => delay,
+                        None => break response,
+                    }
+                }
+                Ok(response) => break response,
+                Err(e) if (idempotent && is_transient(&e)) || e.is_connect() => {
+                    match self.retry.delay(attempt, None) {
+                        Some(delay) => delay,
+                        None => return Err(e.into()),
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            };
+            warn!(
+                target: "megamind::request",
+                "attempt {} for \"{}\" failed, retrying in {:?}",
+                attempt,
+                url,
+                delay
+            );
+            record!("retries", attempt);
+            sleep(delay).await;
+            attempt += 1;
+        };
+        let url = response.url().clone();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
             .await
+            .map_err(|e| e.with_url(url.clone()))?
+            .to_vec();
+        Ok(RawResponse {
+            url,
+            status,
+            headers,
+            body,
+            source: ResponseSource::Network,
+        })
     }
 
-    /// Get referents.
+    /// Make a request at a specified relative endpoint and decode the response.
     ///
     /// # Args
     ///
-    /// * `created_by` - A Genius ID.
-    /// * `associated` - The associated web page or song.
-    /// * `per_page` - A per-page limit.
-    /// * `page` - A page offset, starting at 1.
+    /// * `endpoint` - The endpoint being requested.
+    /// * `method` - The HTTP method.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
+    /// * `body` - An optional JSON body.
     ///
     /// # Returns
     ///
-    /// The referents that are associated with the web page or song
-    /// and/or are created by a user with the given Genius ID.
-    /// Results follow the `per_page` and `page` rules, and there are
-    /// some failure cases that the argument types can't prevent so please
-    /// visit the [Genius documentation](https://docs.genius.com/#referents-h2) for more information.
-    pub async fn referents(
+    /// A [`Response`].
+    /// [`ClientError::Decode`] occurs if the response body doesn't match the data models.
+    async fn request_endpoint<
+        T: DeserializeOwned,
+        S: AsRef<str>,
+        P: Serialize + AsRef<str>,
+        B: Serialize,
+    >(
         &self,
-        created_by: Option<u32>,
-        associated: Option<ReferentAssociation>,
-        per_page: Option<u8>,
-        page: Option<u8>,
-    ) -> Result<Response<ReferentsResponse>, ClientError> {
-        let mut queries = vec![("text_format", String::from("html,plain"))];
-        if let Some(created_by_id) = created_by {
-            queries.push(("created_by_id", created_by_id.to_string()));
-        }
-        if let Some(association) = associated {
-            let params = match association {
-                ReferentAssociation::SongId(id) => ("song_id", id.to_string()),
-                ReferentAssociation::WebPageId(id) => ("web_page_id", id.to_string()),
-            };
-            queries.push(params);
-        }
-        if let Some(per_page) = per_page {
-            queries.push(("per_page", per_page.to_string()));
-        }
-        if let Some(page) = page {
-            queries.push(("page", page.to_string()));
-        }
-        self.get("/referents", &queries).await
+        endpoint: Endpoint,
+        method: Method,
+        path: S,
+        query: &[(&str, P)],
+        body: Option<&B>,
+    ) -> Result<Response<T>, ClientError> {
+        self.recorded(
+            endpoint,
+            self.send(endpoint, method, path, query, body),
+            |raw| decode(raw.url, raw.status, &raw.body),
+        )
+        .await
     }
 
-    /// Get search results.
+    /// Send a request, decode the response, and record the outcome in the client's statistics.
     ///
     /// # Args
     ///
-    /// * `query` - A search term to match against.
+    /// * `endpoint` - The endpoint being requested.
+    /// * `send` - Sends the request.
+    /// * `decode` - Decodes the raw response.
     ///
     /// # Returns
     ///
-    /// Search results associated with the query.
-    pub async fn search<S: AsRef<str>>(
+    /// The decoded response.
+    async fn recorded<T>(
         &self,
-        query: S,
-    ) -> Result<Response<SearchResponse>, ClientError> {
-        self.get("/search", &[("q", query.as_ref())]).await
+        endpoint: Endpoint,
+        send: impl Future<Output = Result<RawResponse, ClientError>>,
+        decode: impl FnOnce(RawResponse) -> Result<Response<T>, ClientError>,
+    ) -> Result<Response<T>, ClientError> {
+        let start = Instant::now();
+        let raw = send.await;
+        let source = match (&raw, &self.cassette) {
+            (Ok(raw), _) => raw.source,
+            (Err(_), Some(cassette)) if cassette.mode() == CassetteMode::Replay => {
+                ResponseSource::Cassette
+            }
+            (Err(_), _) => ResponseSource::Network,
+        };
+        let result = raw.and_then(decode);
+        self.stats
+            .record(endpoint, &result, source, start.elapsed());
+        result
     }
 
-    /// Get a song.
+    /// Make a generic GET request at a specified relative endpoint.
     ///
     /// # Args
     ///
-    /// * `id` - A Genius ID.
+    /// * `endpoint` - The endpoint being requested.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
     ///
     /// # Returns
     ///
-    /// The song associated with the ID.
-    pub async fn song(&self, id: u32) -> Result<Response<SongResponse>, ClientError> {
-        self.get(format!("/songs/{}", id), &[("text_format", "html,plain")])
+    /// A [`Response`].
+    async fn get<T: DeserializeOwned, S: AsRef<str>, P: Serialize + AsRef<str>>(
+        &self,
+        endpoint: Endpoint,
+        path: S,
+        query: &[(&str, P)],
+    ) -> Result<Response<T>, ClientError> {
+        self.request_endpoint(endpoint, Method::GET, path, query, None::<&()>)
             .await
     }
 
-    /// Get a user.
+    /// Make a GET request at any relative endpoint and decode the response.
+    ///
+    /// Useful for endpoints that don't have their own method yet.
+    /// The request goes through the same auth, base URL, retries, rate limiting, caching, and logging as every other endpoint,
+    /// under [`Endpoint::Other`]. Unlike the other endpoints, `text_format` isn't added to the query parameters.
     ///
     /// # Args
     ///
-    /// * `id` - A Genius ID.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
     ///
     /// # Returns
     ///
-    /// The user associated with the ID.
-    pub async fn user(&self, id: u32) -> Result<Response<UserResponse>, ClientError> {
-        self.get(format!("/users/{}", id), &[("text_format", "html,plain")])
-            .await
+    /// A [`Response`] with a payload of any type.
+    /// [`ClientError::Decode`] occurs if the response body doesn't match the payload type.
+    pub async fn get_json<
+        T: DeserializeOwned,
+        S: AsRef<str>,
+        P: Serialize + AsRef<str>,
+    >(
+        &self,
+        path: S,
+        query: &[(&str, P)],
+    ) -> Result<Response<T>, ClientError> {
+        self.get(Endpoint::Other, path, query).await
     }
 
-    /// Get a web page.
+    /// Make a GET request at any relative endpoint without decoding the payload into a data model.
+    ///
+    /// See [`Client::get_json`].
     ///
     /// # Args
     ///
-    /// * `raw_annotatable_url` - The URL as it would appear in a browser.
-    /// * `canonical_url` - The URL as specified by an appropriate <link> tag in a page's <head>.
-    /// * `og_url` - The URL as specified by an og:url <meta> tag in a page's <head>.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
     ///
     /// # Returns
     ///
-    /// The web page associated with the above arguments.
-    pub async fn web_pages(
+    /// A [`Response`] with the raw JSON payload.
+    pub async fn get_value<S: AsRef<str>, P: Serialize + AsRef<str>>(
         &self,
-        raw_annotatable_url: Option<&str>,
-        canonical_url: Option<&str>,
-        og_url: Option<&str>,
-    ) -> Result<Response<WebPageResponse>, ClientError> {
-        let mut queries = Vec::new();
-        if let Some(rau) = raw_annotatable_url {
-            queries.push(("raw_annotatable_url", rau));
-        }
-        if let Some(cu) = canonical_url {
-            queries.push(("canonical_url", cu));
-        }
-        if let Some(ou) = og_url {
-            queries.push(("og_url", ou));
-        }
-        self.get("/web_pages/lookup", &queries).await
-    }
-}
-
-/// A web page or song ID that is associated with a referent.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ReferentAssociation {
-    /// A song via Genius ID.
-    SongId(u32),
-    /// A web page via Genius ID.
-    WebPageId(u32),
-}
-
-/// Builder for [`Client`]s.
-#[derive(Default, Debug, Clone)]
-pub struct ClientBuilder {
-    /// auth token
-    auth_token: Option<String>,
-}
-
-impl ClientBuilder {
-    /// Create a new [`ClientBuilder`].
-    ///
-    /// # Returns
-    ///
-    /// A new [`ClientBuilder`], with the base API URL configured to the production API URL.
-    pub fn new() -> Self {
-        ClientBuilder { auth_token: None }
+        path: S,
+        query: &[(&str, P)],
+    ) -> Result<Response<Value>, ClientError> {
+        self.get(Endpoint::Other, path, query).await
     }
 
-    /// Set the auth token.
+    /// Make a request with any HTTP method at any relative endpoint and decode the response.
     ///
-    /// **Note**: does not protect you from entering invalid tokens (e.g., an empty string, an expired token, token with invalid characters, etc.).
+    /// See [`Client::get_json`]; `POST` requests are only retried if they were rate limited or never connected.
     ///
     /// # Args
     ///
-    /// * `auth_token` - The auth token for API requests.
+    /// * `method` - The HTTP method.
+    /// * `path` - The relative endpoint path; should have "/" prepended.
+    /// * `query` - Any query parameters; matches the signature for [`reqwest::RequestBuilder::query`].
+    /// * `body` - An optional JSON body.
     ///
     /// # Returns
     ///
-    /// The modified [`ClientBuilder`].
-    pub fn auth_token<S: Into<String>>(mut self, auth_token: S) -> Self {
-        self.auth_token = Some(auth_token.into());
-        self
+    /// A [`Response`] with a payload of any type, which can be [`Value`] for the raw JSON payload.
+    /// [`ClientError::Decode`] occurs if the response body doesn't match the payload type.
+    pub async fn request<
+        T: DeserializeOwned,
+        S: AsRef<str>,
+        P: Serialize + AsRef<str>,
+        B: Serialize,
+    >(
+        &self,
+        method: Method,
+        path: S,
+        query: &[(&str, P)],
+        body: Option<&B>,
+    ) -> Result<Response<T>, ClientError> {
+        self.request_endpoint(Endpoint::Other, method, path, query, body)
+            .await
     }
 
-    /// Build a [`Client`].
+    /// Get a snapshot of the client's request statistics.
+    ///
+    /// Statistics are shared by all clones of the client, including those from
+    /// [`Client::bypass_cache`] and [`Client::with_text_format`].
+    ///
+    /// # Returns
+    ///
+    /// The statistics so far, which can be exported with [`Stats::to_prometheus`].
+    pub fn stats(&self) -> Stats {
+        self.stats.snapshot()
+    }
+
+    /// Get a copy of this client that bypasses the cache.
+    ///
+    /// The copy skips cache lookups but still stores fresh responses,
+    /// so it can be used to refresh cached responses for individual calls.
+    ///
+    /// # Returns
+    ///
+    /// A client that always hits the network.
+    pub fn bypass_cache(&self) -> Self {
+        Client {
+            cache_bypass: true,
+            ..self.clone()
+        }
+    }
+
+    /// Get a copy of this client that requests different text formats.
+    ///
+    /// # Args
+    ///
+    /// * `formats` - The text formats; no formats means the default (HTML and plain text).
+    ///
+    /// # Returns
+    ///
+    /// A client that requests the given text formats.
+    pub fn with_text_format<I: IntoIterator<Item = TextFormat>>(
+        &self,
+        formats: I,
+    ) -> Self {
+        Client {
+            text_format: text_format_query(formats),
+            ..self.clone()
+        }
+    }
+
+    /// Get the account info for the currently authed user.
+    ///
+    /// Requires scope: `me`.
+    ///
+    /// # Returns
+    ///
+    /// The current user.
+    pub async fn account(&self) -> Result<Response<AccountResponse>, ClientError> {
+        self.get(
+            Endpoint::Account,
+            "/account",
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get an album.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The album associated with the ID.
+    pub async fn album(&self, id: u32) -> Result<Response<AlbumResponse>, ClientError> {
+        self.get(
+            Endpoint::Album,
+            format!("/albums/{}", id),
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get an album's tracks.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    /// * `per_page` - A per-page limit.
+    /// * `page` - A page offset, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// The tracks on the album associated with the ID, with their track numbers.
+    pub async fn album_tracks(
+        &self,
+        id: u32,
+        per_page: Option<u8>,
+        page: Option<u32>,
+    ) -> Result<Response<AlbumTracksResponse>, ClientError> {
+        let mut queries = Vec::new();
+        if let Some(per_page) = per_page {
+            queries.push(("per_page", per_page.to_string()));
+        }
+        if let Some(page) = page {
+            queries.push(("page", page.to_string()));
+        }
+        self.get(
+            Endpoint::AlbumTracks,
+            format!("/albums/{}/tracks", id),
+            &queries,
+        )
+        .await
+    }
+
+    /// Stream every track on an album, fetching pages lazily.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    /// * `per_page` - A per-page limit.
+    /// * `prefetch` - Whether to fetch the next page while the current one is consumed.
+    ///
+    /// # Returns
+    ///
+    /// A stream of the tracks that would be returned by [`Client::album_tracks`], page by page.
+    /// Pages are fetched until there is no next page, and API errors are folded into [`ClientError::Api`].
+    /// The stream ends after the first error.
+    pub fn album_tracks_stream(
+        &self,
+        id: u32,
+        per_page: Option<u8>,
+        prefetch: bool,
+    ) -> impl Stream<Item = Result<Track, ClientError>> {
+        let client = self.clone();
+        paginate(1, prefetch, move |page| {
+            let client = client.clone();
+            async move {
+                let response = client
+                    .album_tracks(id, per_page, Some(page))
+                    .await
+                    .into_api_result()?;
+                Ok(Page {
+                    items: response.tracks,
+                    next_page: response.next_page,
+                })
+            }
+        })
+    }
+
+    ///  Get an annotation.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The annotation associated with the ID.
+    pub async fn annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.get(
+            Endpoint::Annotation,
+            format!("/annotations/{}", id),
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get annotations in a batch.
+    ///
+    /// # Args
+    ///
+    /// * `ids` - Genius IDs.
+    ///
+    /// # Returns
+    ///
+    /// Each ID paired with the result that [`Client::annotation`] would return, in the same order as `ids`.
+    /// Requests run concurrently, up to the client's concurrency limit, and each failure only affects its own ID.
+    pub async fn annotations<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> Vec<(u32, Result<Response<AnnotationResponse>, ClientError>)> {
+        batch_ordered(ids.into_iter().collect(), self.concurrency, |id| {
+            self.annotation(id)
+        })
+        .await
+    }
+
+    /// Stream annotations in a batch.
+    ///
+    /// # Args
+    ///
+    /// * `ids` - Genius IDs.
+    ///
+    /// # Returns
+    ///
+    /// A stream of each ID paired with the result that [`Client::annotation`] would return, in completion order.
+    /// Requests run concurrently, up to the client's concurrency limit, and each failure only affects its own ID.
+    pub fn annotations_stream<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> impl Stream<Item = (u32, Result<Response<AnnotationResponse>, ClientError>)>
+    {
+        let client = self.clone();
+        batch_unordered(ids.into_iter().collect(), self.concurrency, move |id| {
+            let client = client.clone();
+            async move { client.annotation(id).await }
+        })
+    }
+
+    /// Create an annotation.
+    ///
+    /// Requires scope: `create_annotation`.
+    ///
+    /// # Args
+    ///
+    /// * `annotation` - The annotation content, its referent, and the web page it belongs to.
+    ///
+    /// # Returns
+    ///
+    /// The newly created annotation.
+    pub async fn create_annotation(
+        &self,
+        annotation: &AnnotationPayload,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.request_endpoint(
+            Endpoint::CreateAnnotation,
+            Method::POST,
+            "/annotations",
+            &[("text_format", self.text_format.as_str())],
+            Some(annotation),
+        )
+        .await
+    }
+
+    /// Update an annotation.
+    ///
+    /// Requires scope: `manage_annotation`.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    /// * `annotation` - The annotation content, its referent, and the web page it belongs to.
+    ///
+    /// # Returns
+    ///
+    /// The updated annotation.
+    pub async fn update_annotation(
+        &self,
+        id: u32,
+        annotation: &AnnotationPayload,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.request_endpoint(
+            Endpoint::UpdateAnnotation,
+            Method::PUT,
+            format!("/annotations/{}", id),
+            &[("text_format", self.text_format.as_str())],
+            Some(annotation),
+        )
+        .await
+    }
+
+    /// Delete an annotation.
+    ///
+    /// Requires scope: `manage_annotation`.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// An empty response, since the API doesn't return a payload for a deleted annotation.
+    pub async fn delete_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<()>, ClientError> {
+        self.recorded(
+            Endpoint::DeleteAnnotation,
+            self.send(
+                Endpoint::DeleteAnnotation,
+                Method::DELETE,
+                format!("/annotations/{}", id),
+                &Vec::<(&str, &str)>::new(),
+                None::<&()>,
+            ),
+            |raw| {
+                if raw.status.is_success() {
+                    Ok(Response::Success {
+                        meta: SuccessMeta {
+                            status: raw.status.as_u16(),
+                        },
+                        response: (),
+                    })
+                } else {
+                    decode(raw.url, raw.status, &raw.body)
+                }
+            },
+        )
+        .await
+    }
+
+    /// Upvote an annotation.
+    ///
+    /// Requires scope: `vote`.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The upvoted annotation.
+    /// A missing `vote` scope comes back like any other API error;
+    /// set [`ClientBuilder::scopes`] to catch it before sending.
+    pub async fn upvote_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.vote_annotation(id, "upvote").await
+    }
+
+    /// Downvote an annotation.
+    ///
+    /// Requires scope: `vote`.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The downvoted annotation.
+    /// A missing `vote` scope comes back like any other API error;
+    /// set [`ClientBuilder::scopes`] to catch it before sending.
+    pub async fn downvote_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.vote_annotation(id, "downvote").await
+    }
+
+    /// Remove a vote from an annotation.
+    ///
+    /// Requires scope: `vote`.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The unvoted annotation.
+    /// A missing `vote` scope comes back like any other API error;
+    /// set [`ClientBuilder::scopes`] to catch it before sending.
+    pub async fn unvote_annotation(
+        &self,
+        id: u32,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.vote_annotation(id, "unvote").await
+    }
+
+    /// Vote on an annotation.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    /// * `action` - The vote action; one of "upvote", "downvote", or "unvote".
+    ///
+    /// # Returns
+    ///
+    /// The annotation after the vote.
+    async fn vote_annotation(
+        &self,
+        id: u32,
+        action: &str,
+    ) -> Result<Response<AnnotationResponse>, ClientError> {
+        self.request_endpoint(
+            Endpoint::VoteAnnotation,
+            Method::PUT,
+            format!("/annotations/{}/{}", id, action),
+            &[("text_format", self.text_format.as_str())],
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// Get an artist.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The artist associated with the ID.
+    pub async fn artist(
+        &self,
+        id: u32,
+    ) -> Result<Response<ArtistResponse>, ClientError> {
+        self.get(
+            Endpoint::Artist,
+            format!("/artists/{}", id),
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get artists in a batch.
+    ///
+    /// # Args
+    ///
+    /// * `ids` - Genius IDs.
+    ///
+    /// # Returns
+    ///
+    /// Each ID paired with the result that [`Client::artist`] would return, in the same order as `ids`.
+    /// Requests run concurrently, up to the client's concurrency limit, and each failure only affects its own ID.
+    pub async fn artists<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> Vec<(u32, Result<Response<ArtistResponse>, ClientError>)> {
+        batch_ordered(ids.into_iter().collect(), self.concurrency, |id| {
+            self.artist(id)
+        })
+        .await
+    }
+
+    /// Stream artists in a batch.
+    ///
+    /// # Args
+    ///
+    /// * `ids` - Genius IDs.
+    ///
+    /// # Returns
+    ///
+    /// A stream of each ID paired with the result that [`Client::artist`] would return, in completion order.
+    /// Requests run concurrently, up to the client's concurrency limit, and each failure only affects its own ID.
+    pub fn artists_stream<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> impl Stream<Item = (u32, Result<Response<ArtistResponse>, ClientError>)> {
+        let client = self.clone();
+        batch_unordered(ids.into_iter().collect(), self.concurrency, move |id| {
+            let client = client.clone();
+            async move { client.artist(id).await }
+        })
+    }
+
+    /// Get an artist's songs.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    /// * `sort` - The sort order.
+    /// * `per_page` - A per-page limit.
+    /// * `page` - A page offset, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// The songs by the artist associated with the ID.
+    /// Results follow the `sort`, `per_page`, and `page` rules,
+    /// so please visit the [Genius documentation](https://docs.genius.com/#artists-h2) for more information.
+    pub async fn artist_songs(
+        &self,
+        id: u32,
+        sort: Option<ArtistSongsSort>,
+        per_page: Option<u8>,
+        page: Option<u32>,
+    ) -> Result<Response<ArtistSongsResponse>, ClientError> {
+        let mut queries = Vec::new();
+        if let Some(sort) = sort {
+            let sort = match sort {
+                ArtistSongsSort::Title => "title",
+                ArtistSongsSort::Popularity => "popularity",
+            };
+            queries.push(("sort", String::from(sort)));
+        }
+        if let Some(per_page) = per_page {
+            queries.push(("per_page", per_page.to_string()));
+        }
+        if let Some(page) = page {
+            queries.push(("page", page.to_string()));
+        }
+        self.get(
+            Endpoint::ArtistSongs,
+            format!("/artists/{}/songs", id),
+            &queries,
+        )
+        .await
+    }
+
+    /// Stream every song by an artist, fetching pages lazily.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    /// * `sort` - The sort order.
+    /// * `per_page` - A per-page limit.
+    /// * `prefetch` - Whether to fetch the next page while the current one is consumed.
+    ///
+    /// # Returns
+    ///
+    /// A stream of the songs that would be returned by [`Client::artist_songs`], page by page.
+    /// Pages are fetched until there is no next page, and API errors are folded into [`ClientError::Api`].
+    /// The stream ends after the first error.
+    pub fn artist_songs_stream(
+        &self,
+        id: u32,
+        sort: Option<ArtistSongsSort>,
+        per_page: Option<u8>,
+        prefetch: bool,
+    ) -> impl Stream<Item = Result<SongCoreWithRDC<SongCoreStats>, ClientError>> {
+        let client = self.clone();
+        paginate(1, prefetch, move |page| {
+            let client = client.clone();
+            async move {
+                let response = client
+                    .artist_songs(id, sort, per_page, Some(page))
+                    .await
+                    .into_api_result()?;
+                Ok(Page {
+                    items: response.songs,
+                    next_page: response.next_page,
+                })
+            }
+        })
+    }
+
+    /// Get a referent.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The referent associated with the ID, along with its annotations.
+    pub async fn referent(
+        &self,
+        id: u32,
+    ) -> Result<Response<ReferentResponse>, ClientError> {
+        self.get(
+            Endpoint::Referent,
+            format!("/referents/{}", id),
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get referents.
+    ///
+    /// # Args
+    ///
+    /// * `created_by` - A Genius ID.
+    /// * `associated` - The associated web page or song.
+    /// * `per_page` - A per-page limit.
+    /// * `page` - A page offset, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// The referents that are associated with the web page or song
+    /// and/or are created by a user with the given Genius ID.
+    /// Results follow the `per_page` and `page` rules, and there are
+    /// some failure cases that the argument types can't prevent so please
+    /// visit the [Genius documentation](https://docs.genius.com/#referents-h2) for more information.
+    pub async fn referents(
+        &self,
+        created_by: Option<u32>,
+        associated: Option<ReferentAssociation>,
+        per_page: Option<u8>,
+        page: Option<u32>,
+    ) -> Result<Response<ReferentsResponse>, ClientError> {
+        let mut queries = vec![("text_format", self.text_format.clone())];
+        if let Some(created_by_id) = created_by {
+            queries.push(("created_by_id", created_by_id.to_string()));
+        }
+        if let Some(association) = associated {
+            let params = match association {
+                ReferentAssociation::SongId(id) => ("song_id", id.to_string()),
+                ReferentAssociation::WebPageId(id) => ("web_page_id", id.to_string()),
+            };
+            queries.push(params);
+        }
+        if let Some(per_page) = per_page {
+            queries.push(("per_page", per_page.to_string()));
+        }
+        if let Some(page) = page {
+            queries.push(("page", page.to_string()));
+        }
+        self.get(Endpoint::Referents, "/referents", &queries).await
+    }
+
+    /// Stream every referent, fetching pages lazily.
+    ///
+    /// # Args
+    ///
+    /// * `created_by` - A Genius ID.
+    /// * `associated` - The associated web page or song.
+    /// * `per_page` - A per-page limit.
+    /// * `prefetch` - Whether to fetch the next page while the current one is consumed.
+    ///
+    /// # Returns
+    ///
+    /// A stream of the referents that would be returned by [`Client::referents`], page by page.
+    /// Pages are fetched until an empty page arrives, and API errors are folded into [`ClientError::Api`].
+    /// The stream ends after the first error.
+    pub fn referents_stream(
+        &self,
+        created_by: Option<u32>,
+        associated: Option<ReferentAssociation>,
+        per_page: Option<u8>,
+        prefetch: bool,
+    ) -> impl Stream<Item = Result<Referent, ClientError>> {
+        let client = self.clone();
+        paginate(1, prefetch, move |page| {
+            let client = client.clone();
+            async move {
+                let response = client
+                    .referents(created_by, associated, per_page, Some(page))
+                    .await
+                    .into_api_result()?;
+                Ok(Page {
+                    items: response.referents,
+                    next_page: Some(page + 1),
+                })
+            }
+        })
+    }
+
+    /// Get search results.
+    ///
+    /// # Args
+    ///
+    /// * `query` - A search term to match against.
+    ///
+    /// # Returns
+    ///
+    /// Search results associated with the query.
+    pub async fn search<S: AsRef<str>>(
+        &self,
+        query: S,
+    ) -> Result<Response<SearchResponse>, ClientError> {
+        self.get(Endpoint::Search, "/search", &[("q", query.as_ref())])
+            .await
+    }
+
+    /// Get a song.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The song associated with the ID.
+    pub async fn song(&self, id: u32) -> Result<Response<SongResponse>, ClientError> {
+        self.get(
+            Endpoint::Song,
+            format!("/songs/{}", id),
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get songs in a batch.
+    ///
+    /// # Args
+    ///
+    /// * `ids` - Genius IDs.
+    ///
+    /// # Returns
+    ///
+    /// Each ID paired with the result that [`Client::song`] would return, in the same order as `ids`.
+    /// Requests run concurrently, up to the client's concurrency limit, and each failure only affects its own ID.
+    pub async fn songs<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> Vec<(u32, Result<Response<SongResponse>, ClientError>)> {
+        batch_ordered(ids.into_iter().collect(), self.concurrency, |id| {
+            self.song(id)
+        })
+        .await
+    }
+
+    /// Stream songs in a batch.
+    ///
+    /// # Args
+    ///
+    /// * `ids` - Genius IDs.
+    ///
+    /// # Returns
+    ///
+    /// A stream of each ID paired with the result that [`Client::song`] would return, in completion order.
+    /// Requests run concurrently, up to the client's concurrency limit, and each failure only affects its own ID.
+    pub fn songs_stream<I: IntoIterator<Item = u32>>(
+        &self,
+        ids: I,
+    ) -> impl Stream<Item = (u32, Result<Response<SongResponse>, ClientError>)> {
+        let client = self.clone();
+        batch_unordered(ids.into_iter().collect(), self.concurrency, move |id| {
+            let client = client.clone();
+            async move { client.song(id).await }
+        })
+    }
+
+    /// Get a user.
+    ///
+    /// # Args
+    ///
+    /// * `id` - A Genius ID.
+    ///
+    /// # Returns
+    ///
+    /// The user associated with the ID.
+    pub async fn user(&self, id: u32) -> Result<Response<UserResponse>, ClientError> {
+        self.get(
+            Endpoint::User,
+            format!("/users/{}", id),
+            &[("text_format", self.text_format.as_str())],
+        )
+        .await
+    }
+
+    /// Get a web page.
+    ///
+    /// # Args
+    ///
+    /// * `raw_annotatable_url` - The URL as it would appear in a browser.
+    /// * `canonical_url` - The URL as specified by an appropriate <link> tag in a page's <head>.
+    /// * `og_url` - The URL as specified by an og:url <meta> tag in a page's <head>.
+    ///
+    /// # Returns
+    ///
+    /// The web page associated with the above arguments.
+    pub async fn web_pages(
+        &self,
+        raw_annotatable_url: Option<&str>,
+        canonical_url: Option<&str>,
+        og_url: Option<&str>,
+    ) -> Result<Response<WebPageResponse>, ClientError> {
+        let mut queries = Vec::new();
+        if let Some(rau) = raw_annotatable_url {
+            queries.push(("raw_annotatable_url", rau));
+        }
+        if let Some(cu) = canonical_url {
+            queries.push(("canonical_url", cu));
+        }
+        if let Some(ou) = og_url {
+            queries.push(("og_url", ou));
+        }
+        self.get(Endpoint::WebPages, "/web_pages/lookup", &queries)
+            .await
+    }
+}
+
+/// An API endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Endpoint {
+    /// Get the current user's account.
+    Account,
+    /// Get an album.
+    Album,
+    /// Get an album's tracks.
+    AlbumTracks,
+    /// Get an annotation.
+    Annotation,
+    /// Create an annotation.
+    CreateAnnotation,
+    /// Update an annotation.
+    UpdateAnnotation,
+    /// Delete an annotation.
+    DeleteAnnotation,
+    /// Upvote, downvote, or unvote an annotation.
+    VoteAnnotation,
+    /// Get an artist.
+    Artist,
+    /// Get an artist's songs.
+    ArtistSongs,
+    /// Get a referent.
+    Referent,
+    /// Get referents.
+    Referents,
+    /// Get search results.
+    Search,
+    /// Get a song.
+    Song,
+    /// Get a user.
+    User,
+    /// Get a web page.
+    WebPages,
+    /// Any endpoint requested through [`Client::get_json`], [`Client::get_value`], or [`Client::request`].
+    Other,
+}
+
+impl Endpoint {
+    /// Get the name of the endpoint.
+    ///
+    /// # Returns
+    ///
+    /// A short, snake case name for the endpoint.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Endpoint::Account => "account",
+            Endpoint::Album => "album",
+            Endpoint::AlbumTracks => "album_tracks",
+            Endpoint::Annotation => "annotation",
+            Endpoint::CreateAnnotation => "create_annotation",
+            Endpoint::UpdateAnnotation => "update_annotation",
+            Endpoint::DeleteAnnotation => "delete_annotation",
+            Endpoint::VoteAnnotation => "vote_annotation",
+            Endpoint::Artist => "artist",
+            Endpoint::ArtistSongs => "artist_songs",
+            Endpoint::Referent => "referent",
+            Endpoint::Referents => "referents",
+            Endpoint::Search => "search",
+            Endpoint::Song => "song",
+            Endpoint::User => "user",
+            Endpoint::WebPages => "web_pages",
+            Endpoint::Other => "other",
+        }
+    }
+
+    /// Get the scope required by the endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The required scope, if there is one.
+    pub fn required_scope(&self) -> Option<Scope> {
+        match self {
+            Endpoint::Account => Some(Scope::Me),
+            Endpoint::CreateAnnotation => Some(Scope::CreateAnnotation),
+            Endpoint::UpdateAnnotation | Endpoint::DeleteAnnotation => {
+                Some(Scope::ManageAnnotation)
+            }
+            Endpoint::VoteAnnotation => Some(Scope::Vote),
+            _ => None,
+        }
+    }
+}
+
+/// A web page or song ID that is associated with a referent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferentAssociation {
+    /// A song via Genius ID.
+    SongId(u32),
+    /// A web page via Genius ID.
+    WebPageId(u32),
+}
+
+/// A format for textual content, like descriptions and annotation bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TextFormat {
+    /// A DOM tree, in [`Text::dom`].
+    Dom,
+    /// Unescaped HTML, in [`Text::html`].
+    Html,
+    /// Plain text, in [`Text::plain`].
+    Plain,
+}
+
+impl TextFormat {
+    /// Get the name of the format, as used by the API.
+    ///
+    /// # Returns
+    ///
+    /// The name of the format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextFormat::Dom => "dom",
+            TextFormat::Html => "html",
+            TextFormat::Plain => "plain",
+        }
+    }
+}
+
+/// The `text_format` query value used when no formats are set.
+const DEFAULT_TEXT_FORMAT: &str = "html,plain";
+
+/// Build a `text_format` query value.
+///
+/// # Args
+///
+/// * `formats` - The requested formats.
+///
+/// # Returns
+///
+/// The comma-separated, deduplicated format names,
+/// or the default (`html,plain`) if no formats are given.
+fn text_format_query<I: IntoIterator<Item = TextFormat>>(formats: I) -> String {
+    let formats = formats
+        .into_iter()
+        .collect::<BTreeSet<TextFormat>>()
+        .iter()
+        .map(TextFormat::as_str)
+        .collect::<Vec<&str>>();
+    if formats.is_empty() {
+        String::from(DEFAULT_TEXT_FORMAT)
+    } else {
+        formats.join(",")
+    }
+}
+
+/// The sort order for an artist's songs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArtistSongsSort {
+    /// Alphabetically by title.
+    Title,
+    /// By popularity, most popular first.
+    Popularity,
+}
+
+/// Builder for [`Client`]s.
+#[derive(Default, Debug, Clone)]
+pub struct ClientBuilder {
+    /// auth tokens
+    auth_tokens: Vec<String>,
+    /// token rotation
+    token_rotation: TokenRotation,
+    /// base URL
+    base_url: Option<String>,
+    /// retry policy
+    retry: RetryPolicy,
+    /// rate limit
+    rate_limit: Option<RateLimit>,
+    /// scopes granted to the auth token
+    scopes: Option<Scopes>,
+    /// response cache
+    cache: Option<Cache>,
+    /// cassette to record to or replay from
+    cassette: Option<Cassette>,
+    /// text formats
+    text_format: Option<String>,
+    /// batch concurrency limit
+    concurrency: Option<usize>,
+    /// connect timeout
+    connect_timeout: Option<Duration>,
+    /// total request timeout
+    timeout: Option<Duration>,
+    /// user agent
+    user_agent: Option<String>,
+    /// HTTP proxy
+    proxy: Option<Proxy>,
+    /// maximum idle connections per host
+    pool_max_idle_per_host: Option<usize>,
+    /// idle connection timeout
+    pool_idle_timeout: Option<Duration>,
+    /// preconfigured Reqwest client
+    http_client: Option<ReqwestClient>,
+}
+
+impl ClientBuilder {
+    /// Create a new [`ClientBuilder`].
+    ///
+    /// # Returns
+    ///
+    /// A new [`ClientBuilder`], with the base API URL configured to the production API URL.
+    pub fn new() -> Self {
+        ClientBuilder {
+            auth_tokens: Vec::new(),
+            token_rotation: TokenRotation::default(),
+            base_url: None,
+            retry: RetryPolicy::none(),
+            rate_limit: None,
+            scopes: None,
+            cache: None,
+            cassette: None,
+            text_format: None,
+            concurrency: None,
+            connect_timeout: None,
+            timeout: None,
+            user_agent: None,
+            proxy: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            http_client: None,
+        }
+    }
+
+    /// Set the auth token.
+    ///
+    /// **Note**: does not protect you from entering invalid tokens (e.g., an empty string, an expired token, token with invalid characters, etc.).
+    ///
+    /// # Args
+    ///
+    /// * `auth_token` - The auth token for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn auth_token<S: Into<String>>(mut self, auth_token: S) -> Self {
+        self.auth_tokens = vec![auth_token.into()];
+        self
+    }
+
+    /// Set a pool of auth tokens.
+    ///
+    /// Each request is sent with a token chosen according to the [`TokenRotation`],
+    /// and tokens that get rate limited or rejected are benched for a while.
+    /// A request that gets rate limited or rejected is sent once more with another token if one is
+    /// available, even with [`RetryPolicy::none`].
+    /// Tokens are identified by their index in logs, traces, and [`Stats::tokens`].
+    /// The token that served a particular request is only reported in the `megamind::request` debug log
+    /// and the `token` field of the `megamind.request` span (with the `tracing` feature);
+    /// [`Stats::tokens`] only keeps totals per token.
+    /// Replaces any token set with [`ClientBuilder::auth_token`].
+    ///
+    /// **Note**: does not protect you from entering invalid tokens.
+    ///
+    /// # Args
+    ///
+    /// * `auth_tokens` - The auth tokens for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn auth_tokens<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        auth_tokens: I,
+    ) -> Self {
+        self.auth_tokens = auth_tokens.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the token rotation.
+    ///
+    /// Only matters with more than one auth token. Defaults to [`TokenRotation::default`] if not set.
+    ///
+    /// # Args
+    ///
+    /// * `token_rotation` - How tokens are chosen and benched.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn token_rotation(mut self, token_rotation: TokenRotation) -> Self {
+        self.token_rotation = token_rotation;
+        self
+    }
+
+    /// Set the base URL.
+    ///
+    /// Useful for pointing the client at a mock server, a caching proxy, or a staging host.
+    /// Defaults to [`BASE_URL`] if not set.
+    /// Trailing slashes are trimmed, since endpoints already have "/" prepended.
+    ///
+    /// # Args
+    ///
+    /// * `base_url` - The base URL for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Set the retry policy.
+    ///
+    /// Applies to every endpoint. Defaults to [`RetryPolicy::none`] if not set.
+    ///
+    /// # Args
+    ///
+    /// * `retry` - The retry policy for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the client-side rate limit.
+    ///
+    /// The limit is shared by all clones of the built [`Client`],
+    /// and requests wait for their turn instead of failing.
+    ///
+    /// # Args
+    ///
+    /// * `rate_limit` - The rate limit for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Set the response cache.
+    ///
+    /// The cache is shared by all clones of the built [`Client`].
+    ///
+    /// # Args
+    ///
+    /// * `cache` - The response cache.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Set the cassette to record to or replay from.
+    ///
+    /// Useful for testing without network access: record a cassette once with a real auth token,
+    /// then replay it with any auth token.
+    /// The cassette is shared by all clones of the built [`Client`].
+    ///
+    /// # Args
+    ///
+    /// * `cassette` - The cassette.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Set the text formats.
+    ///
+    /// Requesting fewer formats shrinks responses, since formats that aren't requested are left out.
+    ///
+    /// # Args
+    ///
+    /// * `formats` - The text formats; no formats means the default (HTML and plain text).
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn text_format<I: IntoIterator<Item = TextFormat>>(
+        mut self,
+        formats: I,
+    ) -> Self {
+        self.text_format = Some(text_format_query(formats));
+        self
+    }
+
+    /// Set the concurrency limit for batch methods like [`Client::songs`].
+    ///
+    /// # Args
+    ///
+    /// * `limit` - The maximum number of requests in flight per batch; defaults to 8, and 0 is treated as 1.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = Some(limit.max(1));
+        self
+    }
+
+    /// Set the connect timeout.
+    ///
+    /// Ignored if a preconfigured client is set with [`ClientBuilder::http_client`].
+    ///
+    /// # Args
+    ///
+    /// * `timeout` - The maximum time to wait for a connection.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the request timeout.
+    ///
+    /// The timeout covers each attempt from connecting until the response body has been read,
+    /// so it also bounds slow reads. Retries each get their own timeout.
+    /// Ignored if a preconfigured client is set with [`ClientBuilder::http_client`].
+    ///
+    /// # Args
+    ///
+    /// * `timeout` - The maximum time for a single attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the `User-Agent` header.
+    ///
+    /// Ignored if a preconfigured client is set with [`ClientBuilder::http_client`].
+    ///
+    /// # Args
+    ///
+    /// * `user_agent` - The user agent for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the HTTP proxy.
+    ///
+    /// Ignored if a preconfigured client is set with [`ClientBuilder::http_client`].
+    ///
+    /// # Args
+    ///
+    /// * `proxy` - The proxy for API requests.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set the maximum number of idle connections kept per host.
+    ///
+    /// Ignored if a preconfigured client is set with [`ClientBuilder::http_client`].
+    ///
+    /// # Args
+    ///
+    /// * `max` - The maximum number of idle connections.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set how long idle connections are kept.
+    ///
+    /// Ignored if a preconfigured client is set with [`ClientBuilder::http_client`].
+    ///
+    /// # Args
+    ///
+    /// * `timeout` - The maximum time a connection can stay idle.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set a preconfigured Reqwest client to send requests with.
+    ///
+    /// The auth token is sent with each request, so the client doesn't need any auth headers.
+    /// The other transport options on this builder are ignored in favor of the client's own.
+    ///
+    /// # Args
+    ///
+    /// * `client` - The Reqwest client.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn http_client(mut self, client: ReqwestClient) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Build the internal Reqwest client from the transport options.
+    ///
+    /// # Returns
+    ///
+    /// The preconfigured client if there is one, otherwise a new client.
+    /// [`reqwest::Error`]s can occur if the options are invalid (e.g., the user agent has invalid characters).
+    fn build_http_client(&mut self) -> Result<ReqwestClient, ReqwestError> {
+        if let Some(client) = self.http_client.take() {
+            return Ok(client);
+        }
+        let mut builder = ReqwestClient::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent.as_str());
+        }
+        if let Some(proxy) = self.proxy.take() {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        builder.build()
+    }
+
+    /// Build a blocking [`blocking::Client`].
+    ///
+    /// # Returns
+    /// A configured [`blocking::Client`], driven by its own single-threaded runtime.
+    /// [`ClientBuilderError`]s can occur for the same reasons as [`ClientBuilder::build`],
+    /// or if the runtime can't be created.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<blocking::Client, ClientBuilderError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(blocking::Client::new(self.build()?, runtime))
+    }
+
+    /// Set the scopes granted to the auth token.
+    ///
+    /// If set, requests to endpoints that require a scope outside of this set
+    /// fail with [`ClientError::MissingScope`] before anything is sent.
+    /// If not set, every request is sent and the API decides.
+    ///
+    /// # Args
+    ///
+    /// * `scopes` - The granted scopes.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`ClientBuilder`].
+    pub fn scopes<I: IntoIterator<Item = Scope>>(mut self, scopes: I) -> Self {
+        self.scopes = Some(scopes.into_iter().collect());
+        self
+    }
+
+    /// Build a [`Client`].
     ///
     /// # Returns
     /// A configured [`Client`].
     /// [`ClientBuilderError`]s can occur if the auth token is missing or contains invalid characters.
-    /// [`ClientBuilderError::ReqwestBuilder`] can technically happen but it wouldn't be clear as to why it would occur.
-    pub fn build(self) -> Result<Client, ClientBuilderError> {
-        if let Some(auth_token) = self.auth_token {
-            let mut headers = HeaderMap::new();
-            let mut header_val =
-                HeaderValue::from_str(&format!("Bearer {}", auth_token))?;
-            header_val.set_sensitive(true);
-            headers.insert(AUTHORIZATION, header_val);
+    /// [`ClientBuilderError::ReqwestBuilder`] can occur if the transport options are invalid.
+    pub fn build(mut self) -> Result<Client, ClientBuilderError> {
+        if !self.auth_tokens.is_empty() {
+            let auth_headers = self
+                .auth_tokens
+                .iter()
+                .map(|auth_token| {
+                    let mut auth_header =
+                        HeaderValue::from_str(&format!("Bearer {}", auth_token))?;
+                    auth_header.set_sensitive(true);
+                    Ok(auth_header)
+                })
+                .collect::<Result<Vec<HeaderValue>, InvalidHeaderValue>>()?;
+            let base_url = self
+                .base_url
+                .as_deref()
+                .unwrap_or(BASE_URL)
+                .trim_end_matches('/')
+                .to_string();
             Ok(Client {
-                internal: ReqwestClient::builder().default_headers(headers).build()?,
+                internal: self.build_http_client()?,
+                tokens: TokenPool::new(auth_headers, self.token_rotation),
+                base_url,
+                retry: self.retry,
+                limiter: self.rate_limit.map(RateLimiter::new),
+                scopes: self.scopes,
+                cache: self.cache,
+                cache_bypass: false,
+                cassette: self.cassette,
+                text_format: self
+                    .text_format
+                    .unwrap_or_else(|| String::from(DEFAULT_TEXT_FORMAT)),
+                concurrency: self.concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+                stats: StatsCollector::default(),
             })
         } else {
             Err(ClientBuilderError::MissingAuthToken)
@@ -335,4 +2245,89 @@ pub enum ClientBuilderError {
     /// Invalid value for the [`reqwest::header::AUTHORIZATION`] header.
     #[error("invalid auth header value: {0}")]
     AuthHeaderValue(#[from] InvalidHeaderValue),
+    /// The runtime for a blocking client couldn't be created.
+    ///
+    /// Only occurs with the `blocking` feature, but always declared so that enabling the feature doesn't add a variant.
+    #[error("blocking runtime build error: {0}")]
+    Runtime(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_error_path() {
+        let url = Url::parse("https://api.genius.com/songs/1").unwrap();
+        let body = br#"{"meta":{"status":200},"response":{"song":{"id":"one"}}}"#;
+        let Err(ClientError::Decode(error)) =
+            decode::<SongResponse>(url, StatusCode::OK, body)
+        else {
+            panic!("expected a decode error");
+        };
+        assert!(error.path.starts_with("response.song"));
+        assert_eq!(error.status, StatusCode::OK);
+        assert_eq!(error.excerpt, String::from_utf8_lossy(body));
+    }
+
+    #[test]
+    fn test_text_format_query() {
+        assert_eq!(
+            text_format_query([TextFormat::Plain, TextFormat::Dom, TextFormat::Plain]),
+            "dom,plain"
+        );
+        assert_eq!(text_format_query([]), DEFAULT_TEXT_FORMAT);
+    }
+
+    #[tokio::test]
+    async fn test_missing_scope() {
+        let client = ClientBuilder::new()
+            .auth_token("token")
+            .base_url("http://127.0.0.1:9")
+            .scopes([Scope::Vote])
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.account().await,
+            Err(ClientError::MissingScope(Scope::Me))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cassette_replay() {
+        let path = std::env::temp_dir()
+            .join(format!("megamind-replay-{}.json", std::process::id()));
+        let recorder = Cassette::record(&path);
+        recorder
+            .record_interaction(Interaction {
+                method: String::from("GET"),
+                url: String::from("http://127.0.0.1:9/songs/0"),
+                query: normalize_query(&[("text_format", "html,plain")]),
+                request_body: None,
+                status: 404,
+                headers: [(
+                    String::from("content-type"),
+                    String::from("application/json"),
+                )]
+                .into_iter()
+                .collect(),
+                body: String::from(r#"{"meta":{"status":404,"message":"Not found"}}"#),
+            })
+            .unwrap();
+        let client = ClientBuilder::new()
+            .auth_token("token")
+            .base_url("http://127.0.0.1:9")
+            .cassette(Cassette::replay(&path).unwrap())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.song(0).await.into_api_result(),
+            Err(ClientError::Api(ApiError::NotFound { .. }))
+        ));
+        assert!(matches!(
+            client.song(1).await,
+            Err(ClientError::Cassette(CassetteError::Unmatched { .. }))
+        ));
+        let _ = std::fs::remove_file(path);
+    }
 }
@@ -0,0 +1,613 @@
+//! An in-process fake of the Genius API for tests.
+//!
+//! The [`MockServer`] serves seeded data models from a local port on a background thread,
+//! so it works with both the async [`crate::Client`] and the blocking client.
+//! Responses can be seeded per path or per query (e.g., one for each page), or queued for the next requests only.
+//! Faults can be injected per path to exercise error handling,
+//! and every request is kept so that tests can assert on what was sent.
+//!
+//! ```no_run
+//! use megamind::{mock::{Fault, MockServer}, models::SongResponse, ApiResult};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockServer::start()?;
+//! server.song(1, &SongResponse::default());
+//! server.fault("/annotations/2", Fault::NotFound);
+//! let client = server.client_builder().build()?;
+//! client.song(1).await.into_api_result()?;
+//! assert!(client.annotation(2).await.into_api_result().is_err());
+//! server.assert_requested("GET", "/songs/1", 1);
+//! # Ok(())
+//! # }
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    io::{BufRead, BufReader, Error as IoError, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::spawn,
+};
+
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{models::*, ClientBuilder};
+
+/// A fault to respond with instead of seeded data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// A `404` error response, decoded as [`Response::Error`].
+    NotFound,
+    /// An error response with any status, decoded as [`Response::Error`].
+    Status {
+        /// The HTTP status code.
+        status: u16,
+        /// The error message.
+        message: String,
+    },
+    /// A `429` response, which surfaces as [`crate::ClientError::RateLimited`].
+    RateLimited {
+        /// The `Retry-After` header in seconds, if any.
+        retry_after: Option<u64>,
+    },
+    /// A `401` OAuth error response, decoded as [`Response::Other`].
+    OAuth {
+        /// The name of the error (e.g., `invalid_token`).
+        error: String,
+        /// The description of the error.
+        description: String,
+    },
+    /// A `200` response with a raw body, usually one that doesn't match the data models.
+    Malformed(String),
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+    /// The HTTP method.
+    pub method: String,
+    /// The path, without query parameters.
+    pub path: String,
+    /// The query parameters, in the order they were sent.
+    pub query: Vec<(String, String)>,
+    /// The request headers, with lowercase names.
+    pub headers: BTreeMap<String, String>,
+    /// The raw request body.
+    pub body: String,
+}
+
+impl MockRequest {
+    /// Get a query parameter.
+    ///
+    /// # Args
+    ///
+    /// * `name` - The name of the query parameter.
+    ///
+    /// # Returns
+    ///
+    /// The first value of the query parameter, if it was sent.
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get a header.
+    ///
+    /// # Args
+    ///
+    /// * `name` - The name of the header, in lowercase.
+    ///
+    /// # Returns
+    ///
+    /// The value of the header, if it was sent.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// A seeded response body.
+#[derive(Debug)]
+struct Seeded {
+    /// query parameters that a request must include
+    query: Vec<(String, String)>,
+    /// raw response body
+    body: String,
+}
+
+/// The state of a [`MockServer`].
+#[derive(Debug, Default)]
+struct MockState {
+    /// seeded response bodies by path, for every matching request
+    responses: HashMap<String, Vec<Seeded>>,
+    /// faults by path, for every request
+    faults: HashMap<String, Fault>,
+    /// responses and faults by path, for the next requests only
+    queued: HashMap<String, VecDeque<MockResponse>>,
+    /// received requests
+    requests: Vec<MockRequest>,
+}
+
+/// A response to write back.
+#[derive(Debug)]
+struct MockResponse {
+    /// The HTTP status.
+    status: StatusCode,
+    /// Extra headers.
+    headers: Vec<(&'static str, String)>,
+    /// The response body.
+    body: String,
+}
+
+impl MockState {
+    /// Respond to a request.
+    ///
+    /// Queued responses and faults take precedence over persistent faults, which take precedence over seeded responses.
+    /// Among seeded responses, the one that matches the most query parameters wins.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The request path, without query parameters.
+    /// * `query` - The request query parameters.
+    ///
+    /// # Returns
+    ///
+    /// The response, which is a `404` error if nothing was seeded for the path and query.
+    fn respond(&mut self, path: &str, query: &[(String, String)]) -> MockResponse {
+        if let Some(response) = self.queued.get_mut(path).and_then(VecDeque::pop_front)
+        {
+            return response;
+        }
+        if let Some(fault) = self.faults.get(path) {
+            return fault.clone().into_response();
+        }
+        let seeded = self.responses.get(path).and_then(|responses| {
+            responses
+                .iter()
+                .filter(|seeded| seeded.query.iter().all(|pair| query.contains(pair)))
+                .max_by_key(|seeded| seeded.query.len())
+        });
+        match seeded {
+            Some(seeded) => MockResponse::ok(seeded.body.clone()),
+            None => Fault::NotFound.into_response(),
+        }
+    }
+}
+
+impl MockResponse {
+    /// Create a `200` response.
+    ///
+    /// # Args
+    ///
+    /// * `body` - The raw response body.
+    ///
+    /// # Returns
+    ///
+    /// The response.
+    fn ok(body: String) -> Self {
+        MockResponse {
+            status: StatusCode::OK,
+            headers: Vec::new(),
+            body,
+        }
+    }
+}
+
+/// Wrap a response payload in a successful response envelope.
+///
+/// # Args
+///
+/// * `response` - The response payload.
+///
+/// # Returns
+///
+/// The raw response body.
+///
+/// # Panics
+///
+/// If the response payload can't be serialized.
+fn envelope<T: Serialize>(response: &T) -> String {
+    let response = serde_json::to_value(response).expect("mock response serializes");
+    json!({ "meta": { "status": 200 }, "response": response }).to_string()
+}
+
+impl Fault {
+    /// Turn the fault into a response.
+    ///
+    /// # Returns
+    ///
+    /// A response shaped like the real API's.
+    fn into_response(self) -> MockResponse {
+        let error = |status: u16, message: &str| {
+            json!({ "meta": { "status": status, "message": message } }).to_string()
+        };
+        match self {
+            Fault::NotFound => MockResponse {
+                status: StatusCode::NOT_FOUND,
+                headers: Vec::new(),
+                body: error(404, "Not found"),
+            },
+            Fault::Status { status, message } => MockResponse {
+                status: StatusCode::from_u16(status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                headers: Vec::new(),
+                body: error(status, &message),
+            },
+            Fault::RateLimited { retry_after } => MockResponse {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                headers: retry_after
+                    .map(|seconds| ("Retry-After", seconds.to_string()))
+                    .into_iter()
+                    .collect(),
+                body: error(429, "Too many requests"),
+            },
+            Fault::OAuth { error, description } => MockResponse {
+                status: StatusCode::UNAUTHORIZED,
+                headers: Vec::new(),
+                body: json!({ "error": error, "error_description": description })
+                    .to_string(),
+            },
+            Fault::Malformed(body) => MockResponse::ok(body),
+        }
+    }
+}
+
+/// A fake Genius API server, running until it's dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    /// local address of the server
+    address: SocketAddr,
+    /// shared server state
+    state: Arc<Mutex<MockState>>,
+    /// whether the server should stop accepting connections
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Start a new [`MockServer`] on a free local port.
+    ///
+    /// # Returns
+    ///
+    /// A running [`MockServer`] with nothing seeded.
+    /// [`std::io::Error`]s can occur if no port can be bound.
+    pub fn start() -> Result<Self, IoError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (thread_state, thread_shutdown) = (state.clone(), shutdown.clone());
+        spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = thread_state.clone();
+                    spawn(move || {
+                        let _ = handle(stream, &state);
+                    });
+                }
+            }
+        });
+        Ok(MockServer {
+            address,
+            state,
+            shutdown,
+        })
+    }
+
+    /// Get the base URL of the server.
+    ///
+    /// # Returns
+    ///
+    /// The base URL, for [`ClientBuilder::base_url`].
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Create a [`ClientBuilder`] for the server.
+    ///
+    /// # Returns
+    ///
+    /// A [`ClientBuilder`] with a placeholder auth token and the base URL configured.
+    pub fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new().auth_token("mock").base_url(self.url())
+    }
+
+    /// Lock the server state.
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Seed the response payload for a path.
+    ///
+    /// The payload is wrapped in a successful response envelope and served for every request to the path,
+    /// regardless of method or query parameters, unless a response was seeded for a more specific query.
+    /// Paginated endpoints seeded this way return the same page forever, so seed them with [`MockServer::respond_with_query`] instead.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    /// * `response` - The response payload.
+    ///
+    /// # Panics
+    ///
+    /// If the response payload can't be serialized.
+    pub fn respond<S: Into<String>, T: Serialize>(
+        &self,
+        path: S,
+        response: &T,
+    ) -> &Self {
+        self.seed(path.into(), &[], envelope(response))
+    }
+
+    /// Seed the response payload for requests to a path that include some query parameters.
+    ///
+    /// Useful for paginated endpoints (e.g., `[("page", "2")]`). Other query parameters are ignored.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    /// * `query` - The query parameters a request must include.
+    /// * `response` - The response payload.
+    ///
+    /// # Panics
+    ///
+    /// If the response payload can't be serialized.
+    pub fn respond_with_query<S: Into<String>, T: Serialize>(
+        &self,
+        path: S,
+        query: &[(&str, &str)],
+        response: &T,
+    ) -> &Self {
+        self.seed(path.into(), query, envelope(response))
+    }
+
+    /// Seed a raw `200` response body for a path, without a response envelope.
+    ///
+    /// Useful for endpoints that aren't shaped like the rest of the API (e.g., `/oauth/token`).
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    /// * `body` - The raw response body.
+    pub fn respond_raw<S: Into<String>, B: Into<String>>(
+        &self,
+        path: S,
+        body: B,
+    ) -> &Self {
+        self.seed(path.into(), &[], body.into())
+    }
+
+    /// Queue the response payload for the next request to a path only.
+    ///
+    /// Queued responses and [`MockServer::fault_once`] faults share one queue per path,
+    /// so repeated calls line up responses for consecutive requests.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    /// * `response` - The response payload.
+    ///
+    /// # Panics
+    ///
+    /// If the response payload can't be serialized.
+    pub fn respond_once<S: Into<String>, T: Serialize>(
+        &self,
+        path: S,
+        response: &T,
+    ) -> &Self {
+        self.enqueue(path.into(), MockResponse::ok(envelope(response)))
+    }
+
+    /// Seed a raw response body for a path and query, replacing any previously seeded for the same query.
+    fn seed(&self, path: String, query: &[(&str, &str)], body: String) -> &Self {
+        let query = query
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<(String, String)>>();
+        let mut state = self.lock();
+        let responses = state.responses.entry(path).or_default();
+        responses.retain(|seeded| seeded.query != query);
+        responses.push(Seeded { query, body });
+        self
+    }
+
+    /// Queue a response for the next request to a path.
+    fn enqueue(&self, path: String, response: MockResponse) -> &Self {
+        self.lock()
+            .queued
+            .entry(path)
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Seed the response for `/account`.
+    pub fn account(&self, response: &AccountResponse) -> &Self {
+        self.respond("/account", response)
+    }
+
+    /// Seed the response for `/annotations/:id`.
+    pub fn annotation(&self, id: u32, response: &AnnotationResponse) -> &Self {
+        self.respond(format!("/annotations/{}", id), response)
+    }
+
+    /// Seed the response for `/artists/:id`.
+    pub fn artist(&self, id: u32, response: &ArtistResponse) -> &Self {
+        self.respond(format!("/artists/{}", id), response)
+    }
+
+    /// Seed the response for `/referents`.
+    pub fn referents(&self, response: &ReferentsResponse) -> &Self {
+        self.respond("/referents", response)
+    }
+
+    /// Seed the response for `/search`.
+    pub fn search(&self, response: &SearchResponse) -> &Self {
+        self.respond("/search", response)
+    }
+
+    /// Seed the response for `/songs/:id`.
+    pub fn song(&self, id: u32, response: &SongResponse) -> &Self {
+        self.respond(format!("/songs/{}", id), response)
+    }
+
+    /// Seed the response for `/users/:id`.
+    pub fn user(&self, id: u32, response: &UserResponse) -> &Self {
+        self.respond(format!("/users/{}", id), response)
+    }
+
+    /// Seed the response for `/web_pages/lookup`.
+    pub fn web_page(&self, response: &WebPageResponse) -> &Self {
+        self.respond("/web_pages/lookup", response)
+    }
+
+    /// Inject a fault for every request to a path, until it's cleared.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    /// * `fault` - The fault to respond with.
+    pub fn fault<S: Into<String>>(&self, path: S, fault: Fault) -> &Self {
+        self.lock().faults.insert(path.into(), fault);
+        self
+    }
+
+    /// Inject a fault for the next request to a path only.
+    ///
+    /// Useful for testing retries. Repeated calls queue up faults for consecutive requests,
+    /// in line with any responses queued by [`MockServer::respond_once`].
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    /// * `fault` - The fault to respond with.
+    pub fn fault_once<S: Into<String>>(&self, path: S, fault: Fault) -> &Self {
+        self.enqueue(path.into(), fault.into_response())
+    }
+
+    /// Clear every fault and queued response for a path.
+    ///
+    /// # Args
+    ///
+    /// * `path` - The path, without query parameters; should have "/" prepended.
+    pub fn clear_faults(&self, path: &str) -> &Self {
+        let mut state = self.lock();
+        state.faults.remove(path);
+        state.queued.remove(path);
+        self
+    }
+
+    /// Get every request received so far.
+    ///
+    /// # Returns
+    ///
+    /// The received requests, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Assert that a path was requested a number of times.
+    ///
+    /// # Args
+    ///
+    /// * `method` - The HTTP method.
+    /// * `path` - The path, without query parameters.
+    /// * `times` - The expected number of requests.
+    ///
+    /// # Panics
+    ///
+    /// If the number of matching requests is different.
+    pub fn assert_requested(&self, method: &str, path: &str, times: usize) {
+        let requests = self.requests();
+        let count = requests
+            .iter()
+            .filter(|r| r.method == method && r.path == path)
+            .count();
+        assert_eq!(
+            count, times,
+            "expected {} {} to be requested {} time(s), but it was requested {} time(s); received: {:?}",
+            method, path, times, count, requests
+        );
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake the accept loop so it sees the shutdown flag
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// Handle a single connection.
+///
+/// # Args
+///
+/// * `stream` - The connection.
+/// * `state` - The shared server state.
+///
+/// # Returns
+///
+/// Nothing, or a [`std::io::Error`] if the connection breaks.
+fn handle(mut stream: TcpStream, state: &Mutex<MockState>) -> Result<(), IoError> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let mut headers = BTreeMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        match header.trim_end().split_once(':') {
+            Some((name, value)) => {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+            None => break,
+        }
+    }
+    let length = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let request = MockRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect(),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let response = state.respond(path, &request.query);
+        state.requests.push(request);
+        response
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status.as_u16(),
+        response.status.canonical_reason().unwrap_or(""),
+        response.body.len()
+    );
+    for (name, value) in response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
@@ -0,0 +1,83 @@
+//! Data models for albums.
+//!
+//! The endpoints for retrieving albums aren't officially documented,
+//! but you can visit the [Genius documentation](https://docs.genius.com/#songs-h2)
+//! for context on the response format via the album data nested in songs.
+#[cfg(feature = "catchall")]
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "catchall")]
+use serde_json::Value;
+
+use super::{
+    Album, DateComponents, Performance, Referent, SongCoreStats, SongCoreWithRDC,
+};
+
+/// An album response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AlbumResponse {
+    /// Album data.
+    pub album: AlbumDetails,
+}
+
+/// Full album data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AlbumDetails {
+    /// Total number of comments.
+    pub comment_count: u32,
+    /// URL for the cover art thumbnail.
+    pub cover_art_thumbnail_url: String,
+    /// URL for a custom header image.
+    pub custom_header_image_url: Option<String>,
+    /// URL to the header image.
+    pub header_image_url: String,
+    /// State of the album lock.
+    pub lock_state: String,
+    /// Total number of pyongs.
+    pub pyongs_count: Option<u32>,
+    /// Release date.
+    pub release_date: Option<String>,
+    /// The album's release date, split into components.
+    pub release_date_components: Option<DateComponents>,
+    /// Total number of page views across the album's songs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_pageviews: Option<u32>,
+    /// Total number of songs on the album.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub song_count: Option<u32>,
+    /// Album description as an annotation.
+    pub description_annotation: Referent,
+    /// Performance credits across the album.
+    pub song_performances: Vec<Performance>,
+    /// Core album data.
+    #[serde(flatten)]
+    pub core: Album,
+    /// Extra data.
+    #[cfg(feature = "catchall")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// An album tracks response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AlbumTracksResponse {
+    /// Tracks on the album.
+    pub tracks: Vec<Track>,
+    /// The next page offset, if there is one.
+    pub next_page: Option<u32>,
+}
+
+/// A track on an album.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Track {
+    /// The track number, if the album has an ordering.
+    pub number: Option<u32>,
+    /// The song on the track.
+    pub song: SongCoreWithRDC<SongCoreStats>,
+    /// Extra data.
+    #[cfg(feature = "catchall")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
@@ -24,6 +24,70 @@ pub struct AnnotationResponse {
     pub referent: ReferentCore,
 }
 
+/// A request payload for creating or updating an annotation.
+///
+/// Visit the [Genius documentation](https://docs.genius.com/#annotations-h2) for more context on each field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AnnotationPayload {
+    /// The annotation content.
+    pub annotation: AnnotationContent,
+    /// The referent that the annotation is attached to.
+    pub referent: ReferentPayload,
+    /// The web page that the referent is on.
+    pub web_page: WebPagePayload,
+}
+
+/// Annotation content for a request payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct AnnotationContent {
+    /// The body of the annotation.
+    pub body: MarkdownBody,
+}
+
+/// A Markdown body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct MarkdownBody {
+    /// The text for the body, in Markdown.
+    pub markdown: String,
+}
+
+/// A referent for a request payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ReferentPayload {
+    /// The URL of the page, as it would appear in a browser.
+    pub raw_annotatable_url: String,
+    /// The highlighted fragment of the page.
+    pub fragment: String,
+    /// The context surrounding the fragment, used to find the fragment on the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_for_display: Option<ReferentContext>,
+}
+
+/// The context surrounding a referent fragment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ReferentContext {
+    /// The HTML immediately before the fragment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_html: Option<String>,
+    /// The HTML immediately after the fragment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_html: Option<String>,
+}
+
+/// A web page for a request payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct WebPagePayload {
+    /// The URL as specified by an appropriate <link> tag in a page's <head>.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+    /// The URL as specified by an og:url <meta> tag in a page's <head>.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub og_url: Option<String>,
+    /// The title of the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
 /// Annotation data.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct Annotation {
@@ -9,7 +9,9 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "catchall")]
 use serde_json::Value;
 
-use super::{Referent, Text, UserCore, UserInteractionMetadata};
+use super::{
+    Referent, SongCoreStats, SongCoreWithRDC, Text, UserCore, UserInteractionMetadata,
+};
 
 /// An artist response.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
@@ -18,6 +20,15 @@ pub struct ArtistResponse {
     pub artist: Artist,
 }
 
+/// An artist songs response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ArtistSongsResponse {
+    /// Songs by the artist.
+    pub songs: Vec<SongCoreWithRDC<SongCoreStats>>,
+    /// The next page offset, if there is one.
+    pub next_page: Option<u32>,
+}
+
 /// Artist data.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct Artist {
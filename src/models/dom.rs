@@ -0,0 +1,695 @@
+//! Data models for the DOM representation of textual content.
+//!
+//! Returned in [`super::Text::dom`] when the `dom` text format is requested.
+//! Visit the [Genius documentation](https://docs.genius.com/#/response-format-h1) for more context.
+//!
+//! Recognized tags are typed as [`ElementKind`]s, and trees can be rendered
+//! as plain text, Markdown, or ANSI-styled terminal output.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A node in a DOM tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawNode", into = "RawNode")]
+pub enum DomNode {
+    /// A run of text.
+    Text(String),
+    /// An element with a kind and children.
+    Element(DomElement),
+}
+
+impl Default for DomNode {
+    fn default() -> Self {
+        DomNode::Text(String::new())
+    }
+}
+
+/// An element in a DOM tree.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DomElement {
+    /// The kind of element, based on its tag.
+    pub kind: ElementKind,
+    /// HTML attributes, minus any that are part of the kind.
+    pub attributes: BTreeMap<String, Value>,
+    /// Genius-specific data (e.g., the ID of a linked annotation).
+    pub data: BTreeMap<String, Value>,
+    /// Child nodes.
+    pub children: Vec<DomNode>,
+}
+
+/// The kind of a DOM element.
+///
+/// Aliased tags are normalized when serialized (e.g., `b` becomes `strong`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ElementKind {
+    /// The root of the tree (`root`).
+    #[default]
+    Root,
+    /// A paragraph (`p`).
+    Paragraph,
+    /// A heading (`h1` through `h6`).
+    Heading(u8),
+    /// A block quote (`blockquote`).
+    Blockquote,
+    /// A list (`ul` or `ol`).
+    List {
+        /// Whether the list is numbered.
+        ordered: bool,
+    },
+    /// A list item (`li`).
+    ListItem,
+    /// A link (`a`).
+    Link {
+        /// The link target.
+        href: String,
+    },
+    /// An image (`img`).
+    Image {
+        /// The image source.
+        src: String,
+        /// The alternate text, if any.
+        alt: Option<String>,
+    },
+    /// Embedded media (`iframe`, `video`, `audio`, or `embed`).
+    Embed {
+        /// The original tag.
+        tag: String,
+        /// The media source, if any.
+        src: Option<String>,
+    },
+    /// Bold text (`strong` or `b`).
+    Bold,
+    /// Italic text (`em` or `i`).
+    Italic,
+    /// Underlined text (`u`).
+    Underline,
+    /// Struck-through text (`s`, `del`, or `strike`).
+    Strikethrough,
+    /// Inline code (`code`).
+    Code,
+    /// A line break (`br`).
+    LineBreak,
+    /// A horizontal rule (`hr`).
+    HorizontalRule,
+    /// Any other tag, whose children are rendered as-is.
+    Other(String),
+}
+
+/// A DOM node, as sent by the API.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawNode {
+    /// A run of text.
+    Text(String),
+    /// An element.
+    Element(RawElement),
+}
+
+/// A DOM element, as sent by the API.
+#[derive(Serialize, Deserialize)]
+struct RawElement {
+    /// The tag name.
+    tag: String,
+    /// HTML attributes.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, Value>,
+    /// Genius-specific data.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    data: BTreeMap<String, Value>,
+    /// Child nodes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<DomNode>,
+}
+
+impl From<RawNode> for DomNode {
+    fn from(raw: RawNode) -> Self {
+        let mut element = match raw {
+            RawNode::Text(text) => return DomNode::Text(text),
+            RawNode::Element(element) => element,
+        };
+        let mut take = |name: &str| match element.attributes.remove(name) {
+            Some(Value::String(value)) => Some(value),
+            Some(value) => Some(value.to_string()),
+            None => None,
+        };
+        let kind = match element.tag.as_str() {
+            "root" => ElementKind::Root,
+            "p" => ElementKind::Paragraph,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                ElementKind::Heading(element.tag[1..].parse().unwrap_or(1))
+            }
+            "blockquote" => ElementKind::Blockquote,
+            "ul" => ElementKind::List { ordered: false },
+            "ol" => ElementKind::List { ordered: true },
+            "li" => ElementKind::ListItem,
+            "a" => ElementKind::Link {
+                href: take("href").unwrap_or_default(),
+            },
+            "img" => ElementKind::Image {
+                src: take("src").unwrap_or_default(),
+                alt: take("alt"),
+            },
+            "iframe" | "video" | "audio" | "embed" => ElementKind::Embed {
+                src: take("src"),
+                tag: element.tag.clone(),
+            },
+            "strong" | "b" => ElementKind::Bold,
+            "em" | "i" => ElementKind::Italic,
+            "u" => ElementKind::Underline,
+            "s" | "del" | "strike" => ElementKind::Strikethrough,
+            "code" => ElementKind::Code,
+            "br" => ElementKind::LineBreak,
+            "hr" => ElementKind::HorizontalRule,
+            other => ElementKind::Other(other.to_string()),
+        };
+        DomNode::Element(DomElement {
+            kind,
+            attributes: element.attributes,
+            data: element.data,
+            children: element.children,
+        })
+    }
+}
+
+impl From<DomNode> for RawNode {
+    fn from(node: DomNode) -> Self {
+        let element = match node {
+            DomNode::Text(text) => return RawNode::Text(text),
+            DomNode::Element(element) => element,
+        };
+        let mut attributes = element.attributes;
+        let mut put = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                attributes.insert(name.to_string(), Value::String(value));
+            }
+        };
+        let tag = match element.kind {
+            ElementKind::Root => String::from("root"),
+            ElementKind::Paragraph => String::from("p"),
+            ElementKind::Heading(level) => format!("h{}", level),
+            ElementKind::Blockquote => String::from("blockquote"),
+            ElementKind::List { ordered: false } => String::from("ul"),
+            ElementKind::List { ordered: true } => String::from("ol"),
+            ElementKind::ListItem => String::from("li"),
+            ElementKind::Link { href } => {
+                put("href", Some(href).filter(|href| !href.is_empty()));
+                String::from("a")
+            }
+            ElementKind::Image { src, alt } => {
+                put("src", Some(src).filter(|src| !src.is_empty()));
+                put("alt", alt);
+                String::from("img")
+            }
+            ElementKind::Embed { tag, src } => {
+                put("src", src);
+                tag
+            }
+            ElementKind::Bold => String::from("strong"),
+            ElementKind::Italic => String::from("em"),
+            ElementKind::Underline => String::from("u"),
+            ElementKind::Strikethrough => String::from("s"),
+            ElementKind::Code => String::from("code"),
+            ElementKind::LineBreak => String::from("br"),
+            ElementKind::HorizontalRule => String::from("hr"),
+            ElementKind::Other(tag) => tag,
+        };
+        RawNode::Element(RawElement {
+            tag,
+            attributes,
+            data: element.data,
+            children: element.children,
+        })
+    }
+}
+
+/// An output style for rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    /// Plain text.
+    Plain,
+    /// Markdown.
+    Markdown,
+    /// ANSI-styled terminal output.
+    Ansi,
+}
+
+impl DomNode {
+    /// Render the tree as plain text.
+    ///
+    /// # Returns
+    ///
+    /// The text content, with blocks separated by blank lines.
+    pub fn to_plain(&self) -> String {
+        render(self, Style::Plain)
+    }
+
+    /// Render the tree as Markdown.
+    ///
+    /// # Returns
+    ///
+    /// The Markdown equivalent of the tree; underlines are dropped since Markdown has no syntax for them.
+    pub fn to_markdown(&self) -> String {
+        render(self, Style::Markdown)
+    }
+
+    /// Render the tree as ANSI-styled terminal output.
+    ///
+    /// # Returns
+    ///
+    /// The text content with ANSI escape codes for formatting,
+    /// and OSC 8 hyperlinks for links.
+    /// Control characters in text and URLs are stripped, since annotations are user-written.
+    pub fn to_ansi(&self) -> String {
+        render(self, Style::Ansi)
+    }
+}
+
+/// Render a tree.
+///
+/// # Args
+///
+/// * `node` - The root of the tree.
+/// * `style` - The output style.
+///
+/// # Returns
+///
+/// The rendered tree, without leading or trailing whitespace.
+fn render(node: &DomNode, style: Style) -> String {
+    let mut out = String::new();
+    render_node(node, style, false, &mut out);
+    collapse_blank_lines(out.trim())
+}
+
+/// Render a node into a buffer.
+///
+/// # Args
+///
+/// * `node` - The node.
+/// * `style` - The output style.
+/// * `in_code` - Whether the node is inside inline code, where Markdown isn't escaped.
+/// * `out` - The buffer.
+fn render_node(node: &DomNode, style: Style, in_code: bool, out: &mut String) {
+    let element = match node {
+        DomNode::Text(text) if style == Style::Markdown && !in_code => {
+            return out.push_str(&escape_markdown(text));
+        }
+        DomNode::Text(text) if style == Style::Ansi => {
+            return out.push_str(&strip_controls(text, true));
+        }
+        DomNode::Text(text) => return out.push_str(text),
+        DomNode::Element(element) => element,
+    };
+    let in_code = in_code || element.kind == ElementKind::Code;
+    let inner = || {
+        let mut inner = String::new();
+        for child in &element.children {
+            render_node(child, style, in_code, &mut inner);
+        }
+        inner
+    };
+    let wrap = |out: &mut String, ansi: (&str, &str), markdown: (&str, &str)| {
+        let (open, close) = match style {
+            Style::Plain => ("", ""),
+            Style::Markdown => markdown,
+            Style::Ansi => ansi,
+        };
+        out.push_str(open);
+        out.push_str(&inner());
+        out.push_str(close);
+    };
+    match &element.kind {
+        ElementKind::Root | ElementKind::Other(_) => out.push_str(&inner()),
+        ElementKind::Paragraph => {
+            out.push_str(inner().trim());
+            out.push_str("\n\n");
+        }
+        ElementKind::Heading(level) => {
+            match style {
+                Style::Plain => out.push_str(inner().trim()),
+                Style::Markdown => out.push_str(&format!(
+                    "{} {}",
+                    "#".repeat(*level as usize),
+                    inner().trim()
+                )),
+                Style::Ansi => {
+                    out.push_str(&format!("\x1b[1m{}\x1b[22m", inner().trim()))
+                }
+            }
+            out.push_str("\n\n");
+        }
+        ElementKind::Blockquote => {
+            let prefix = match style {
+                Style::Plain => "",
+                Style::Markdown => "> ",
+                Style::Ansi => "\x1b[2m│\x1b[22m ",
+            };
+            let quoted = collapse_blank_lines(inner().trim())
+                .lines()
+                .map(|line| format!("{}{}", prefix, line).trim_end().to_string())
+                .collect::<Vec<String>>()
+                .join("\n");
+            out.push_str(&quoted);
+            out.push_str("\n\n");
+        }
+        ElementKind::List { ordered } => {
+            let mut number = 0;
+            for child in &element.children {
+                let mut item = String::new();
+                match child {
+                    DomNode::Element(DomElement {
+                        kind: ElementKind::ListItem,
+                        children,
+                        ..
+                    }) => {
+                        for child in children {
+                            render_node(child, style, in_code, &mut item);
+                        }
+                    }
+                    DomNode::Text(text) if text.trim().is_empty() => continue,
+                    other => render_node(other, style, in_code, &mut item),
+                }
+                number += 1;
+                let marker = match (ordered, style) {
+                    (true, _) => format!("{}. ", number),
+                    (false, Style::Ansi) => String::from("• "),
+                    (false, _) => String::from("- "),
+                };
+                let indent = " ".repeat(marker.chars().count());
+                let item = collapse_blank_lines(item.trim())
+                    .replace('\n', &format!("\n{}", indent));
+                out.push_str(&marker);
+                out.push_str(&item);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        ElementKind::ListItem => {
+            out.push_str("- ");
+            out.push_str(inner().trim());
+            out.push('\n');
+        }
+        ElementKind::Link { href } => match style {
+            Style::Plain => out.push_str(&inner()),
+            Style::Markdown => {
+                out.push_str(&format!("[{}]({})", inner(), markdown_destination(href)))
+            }
+            Style::Ansi => out.push_str(&format!(
+                "\x1b]8;;{}\x1b\\\x1b[4m{}\x1b[24m\x1b]8;;\x1b\\",
+                strip_controls(href, false),
+                inner()
+            )),
+        },
+        ElementKind::Image { src, alt } => {
+            let alt = alt.as_deref().unwrap_or_default();
+            match style {
+                Style::Plain => out.push_str(alt),
+                Style::Markdown => out.push_str(&format!(
+                    "![{}]({})",
+                    escape_markdown(alt),
+                    markdown_destination(src)
+                )),
+                Style::Ansi => out.push_str(&format!(
+                    "\x1b[2m[image: {}]\x1b[22m",
+                    strip_controls(alt, false)
+                )),
+            }
+        }
+        ElementKind::Embed { src, .. } => {
+            let src = src.as_deref().unwrap_or_default();
+            match style {
+                Style::Plain => out.push_str(src),
+                Style::Markdown if !src.is_empty() => {
+                    let destination = markdown_destination(src);
+                    if destination.starts_with('<') {
+                        out.push_str(&format!(
+                            "[{}]({})",
+                            escape_markdown(&strip_controls(src, false)),
+                            destination
+                        ))
+                    } else {
+                        out.push_str(&format!("<{}>", destination))
+                    }
+                }
+                Style::Markdown => {}
+                Style::Ansi => out.push_str(&format!(
+                    "\x1b[2m[embed: {}]\x1b[22m",
+                    strip_controls(src, false)
+                )),
+            }
+            out.push_str("\n\n");
+        }
+        ElementKind::Bold => wrap(out, ("\x1b[1m", "\x1b[22m"), ("**", "**")),
+        ElementKind::Italic => wrap(out, ("\x1b[3m", "\x1b[23m"), ("*", "*")),
+        ElementKind::Underline => wrap(out, ("\x1b[4m", "\x1b[24m"), ("", "")),
+        ElementKind::Strikethrough => wrap(out, ("\x1b[9m", "\x1b[29m"), ("~~", "~~")),
+        ElementKind::Code if style == Style::Markdown => {
+            out.push_str(&markdown_code(&inner()))
+        }
+        ElementKind::Code => wrap(out, ("\x1b[2m", "\x1b[22m"), ("`", "`")),
+        ElementKind::LineBreak => out.push_str(match style {
+            Style::Markdown => "  \n",
+            _ => "\n",
+        }),
+        ElementKind::HorizontalRule => out.push_str(match style {
+            Style::Ansi => "\x1b[2m────────\x1b[22m\n\n",
+            _ => "---\n\n",
+        }),
+    }
+}
+
+/// Remove control characters, which could otherwise inject terminal escape sequences.
+///
+/// # Args
+///
+/// * `text` - The text to clean.
+/// * `keep_whitespace` - Whether to keep newlines and tabs.
+///
+/// # Returns
+///
+/// The text without C0 or C1 control characters.
+fn strip_controls(text: &str, keep_whitespace: bool) -> String {
+    text.chars()
+        .filter(|&c| !c.is_control() || (keep_whitespace && matches!(c, '\n' | '\t')))
+        .collect()
+}
+
+/// Format a URL as a Markdown link destination.
+///
+/// # Args
+///
+/// * `url` - The URL.
+///
+/// # Returns
+///
+/// The URL, wrapped in angle brackets if it has whitespace or parentheses that would end the link early.
+fn markdown_destination(url: &str) -> String {
+    let url = strip_controls(url, false);
+    if url.contains(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '<' | '>')) {
+        format!(
+            "<{}>",
+            url.replace('\\', "\\\\")
+                .replace('<', "\\<")
+                .replace('>', "\\>")
+        )
+    } else {
+        url
+    }
+}
+
+/// Format text as a Markdown code span.
+///
+/// # Args
+///
+/// * `code` - The text.
+///
+/// # Returns
+///
+/// The text, fenced with more backticks than any run inside it so that the span doesn't end early.
+fn markdown_code(code: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest + 1);
+    let pad = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", fence, pad, code, pad, fence)
+}
+
+/// Escape the characters that Markdown treats as formatting.
+///
+/// # Args
+///
+/// * `text` - The text to escape.
+///
+/// # Returns
+///
+/// The escaped text.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Collapse runs of blank lines into single blank lines.
+///
+/// # Args
+///
+/// * `text` - The text to collapse.
+///
+/// # Returns
+///
+/// The collapsed text.
+fn collapse_blank_lines(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut newlines = 0;
+    for c in text.chars() {
+        if c == '\n' {
+            newlines += 1;
+            if newlines > 2 {
+                continue;
+            }
+        } else {
+            newlines = 0;
+        }
+        collapsed.push(c);
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn tree() -> DomNode {
+        serde_json::from_value(json!({
+            "tag": "root",
+            "children": [
+                {
+                    "tag": "p",
+                    "children": [
+                        "Read ",
+                        {"tag": "a", "attributes": {"href": "https://genius.com", "rel": "noopener"}, "data": {"api_path": "/songs/1"}, "children": ["this"]},
+                        " ",
+                        {"tag": "b", "children": ["now"]},
+                        {"tag": "br"},
+                        {"tag": "em", "children": ["a_b"]}
+                    ]
+                },
+                {"tag": "blockquote", "children": [{"tag": "p", "children": ["quoted"]}]},
+                {"tag": "ul", "children": [{"tag": "li", "children": ["one"]}, {"tag": "li", "children": ["two"]}]},
+                {"tag": "p", "children": [{"tag": "img", "attributes": {"src": "https://images.genius.com/x.png", "alt": "cover", "width": 100}}]},
+                {"tag": "iframe", "attributes": {"src": "https://youtube.com/embed/x"}}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dom_typed_nodes() {
+        let DomNode::Element(root) = tree() else {
+            panic!("expected an element");
+        };
+        assert_eq!(root.kind, ElementKind::Root);
+        let DomNode::Element(paragraph) = &root.children[0] else {
+            panic!("expected an element");
+        };
+        let DomNode::Element(link) = &paragraph.children[1] else {
+            panic!("expected an element");
+        };
+        assert_eq!(
+            link.kind,
+            ElementKind::Link {
+                href: String::from("https://genius.com")
+            }
+        );
+        assert_eq!(link.attributes.get("rel"), Some(&json!("noopener")));
+        assert_eq!(link.data.get("api_path"), Some(&json!("/songs/1")));
+        let DomNode::Element(DomElement { children, .. }) = &root.children[3] else {
+            panic!("expected an element");
+        };
+        let DomNode::Element(image) = &children[0] else {
+            panic!("expected an element");
+        };
+        assert_eq!(
+            image.kind,
+            ElementKind::Image {
+                src: String::from("https://images.genius.com/x.png"),
+                alt: Some(String::from("cover"))
+            }
+        );
+        let round_trip =
+            serde_json::from_value::<DomNode>(serde_json::to_value(tree()).unwrap());
+        assert_eq!(round_trip.unwrap(), tree());
+    }
+
+    #[test]
+    fn test_dom_render() {
+        assert_eq!(
+            tree().to_plain(),
+            "Read this now\na_b\n\nquoted\n\n- one\n- two\n\ncover\n\nhttps://youtube.com/embed/x"
+        );
+        assert_eq!(
+            tree().to_markdown(),
+            "Read [this](https://genius.com) **now**  \n*a\\_b*\n\n> quoted\n\n- one\n- two\n\n![cover](https://images.genius.com/x.png)\n\n<https://youtube.com/embed/x>"
+        );
+        let ansi = tree().to_ansi();
+        assert!(ansi.contains("\x1b[1mnow\x1b[22m"));
+        assert!(ansi.contains("\x1b]8;;https://genius.com\x1b\\"));
+        assert!(ansi.contains("• one"));
+    }
+
+    #[test]
+    fn test_dom_untrusted_links() {
+        let node = serde_json::from_value::<DomNode>(json!({
+            "tag": "p",
+            "children": [
+                "evil\u{1b}[2J\u{9b}31m\u{7}",
+                {"tag": "a", "attributes": {"href": "https://x.com/a b)\u{1b}]8;;\u{7}"}, "children": ["link"]}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            node.to_ansi(),
+            "evil[2J31m\x1b]8;;https://x.com/a b)]8;;\x1b\\\x1b[4mlink\x1b[24m\x1b]8;;\x1b\\"
+        );
+        assert!(node
+            .to_markdown()
+            .ends_with("[link](<https://x.com/a b)]8;;>)"));
+        let embed = serde_json::from_value::<DomNode>(json!({
+            "tag": "iframe",
+            "attributes": {"src": "x>\n\n[click me](javascript:alert(1))"}
+        }))
+        .unwrap();
+        assert_eq!(
+            embed.to_markdown(),
+            "[x\\>\\[click me\\](javascript:alert(1))](<x\\>[click me](javascript:alert(1))>)"
+        );
+        let code = serde_json::from_value::<DomNode>(json!({
+            "tag": "p",
+            "children": [
+                {"tag": "code", "children": ["a`b"]},
+                " ",
+                {"tag": "code", "children": ["`"]}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(code.to_markdown(), "``a`b`` `` ` ``");
+    }
+}
@@ -1,12 +1,17 @@
 //! Data models for the API endpoints.
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 pub mod account;
 pub use account::*;
+pub mod album;
+pub use album::*;
 pub mod annotation;
 pub use annotation::*;
 pub mod artist;
 pub use artist::*;
+pub mod dom;
+pub use dom::*;
 pub mod metadata;
 pub use metadata::*;
 pub mod referent;
@@ -52,6 +57,129 @@ pub enum Response<T> {
     },
 }
 
+impl<T> Response<T> {
+    /// Convert the response into a [`Result`].
+    ///
+    /// # Returns
+    ///
+    /// The response payload if the request was successful, otherwise a typed [`ApiError`].
+    pub fn into_result(self) -> Result<T, ApiError> {
+        match self {
+            Response::Success { response, .. } => Ok(response),
+            Response::Error { meta, response } => {
+                Err(ApiError::from_error(meta, response))
+            }
+            Response::Other {
+                error,
+                error_description,
+            } => Err(ApiError::from_other(error, error_description)),
+        }
+    }
+}
+
+/// A typed error returned by the API.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ApiError {
+    /// The requested resource doesn't exist.
+    #[error("not found: {message}")]
+    NotFound {
+        /// The error message.
+        message: String,
+    },
+    /// The request is missing valid credentials.
+    #[error("unauthorized: {message}")]
+    Unauthorized {
+        /// The error message.
+        message: String,
+    },
+    /// The request isn't allowed (e.g., editing someone else's annotation).
+    #[error("forbidden: {message}")]
+    Forbidden {
+        /// The error message.
+        message: String,
+    },
+    /// The auth token doesn't have the scope required by the endpoint.
+    #[error("insufficient scope: {message}")]
+    InsufficientScope {
+        /// The error message.
+        message: String,
+    },
+    /// The auth token is expired, revoked, or malformed.
+    #[error("invalid token: {message}")]
+    InvalidToken {
+        /// The error message.
+        message: String,
+    },
+    /// Any other API error.
+    #[error("API error ({error}): {message}")]
+    Other {
+        /// The HTTP status code, or the name of the error if there isn't one.
+        error: String,
+        /// The error message.
+        message: String,
+    },
+}
+
+impl ApiError {
+    /// Create an [`ApiError`] from the parts of a [`Response::Error`].
+    ///
+    /// # Args
+    ///
+    /// * `meta` - The response metadata.
+    /// * `response` - An optional error response payload.
+    ///
+    /// # Returns
+    ///
+    /// An [`ApiError`] based on the name of the error in the payload, if it's a known one,
+    /// otherwise on the HTTP status code.
+    pub fn from_error(meta: ErrorMeta, response: Option<ErrorResponse>) -> Self {
+        let error = response.map(|response| response.error).unwrap_or_default();
+        let message = match error.as_str() {
+            "" => meta.message,
+            error => format!("{} ({})", meta.message, error),
+        };
+        match (meta.status, error.as_str()) {
+            (_, "invalid_token") => ApiError::InvalidToken { message },
+            (_, "insufficient_scope") => ApiError::InsufficientScope { message },
+            (401, _) => ApiError::Unauthorized { message },
+            (403, _) => ApiError::Forbidden { message },
+            (404, _) => ApiError::NotFound { message },
+            (status, _) => ApiError::Other {
+                error: status.to_string(),
+                message,
+            },
+        }
+    }
+
+    /// Create an [`ApiError`] from the parts of a [`Response::Other`].
+    ///
+    /// # Args
+    ///
+    /// * `error` - The name of the error.
+    /// * `error_description` - The description of the error.
+    ///
+    /// # Returns
+    ///
+    /// An [`ApiError`] based on the name of the error.
+    pub fn from_other(error: String, error_description: String) -> Self {
+        let message = error_description;
+        match error.as_str() {
+            "invalid_token" => ApiError::InvalidToken { message },
+            "insufficient_scope" => ApiError::InsufficientScope { message },
+            "unauthorized" | "unauthorized_client" | "access_denied" => {
+                ApiError::Unauthorized { message }
+            }
+            _ => ApiError::Other { error, message },
+        }
+    }
+}
+
+impl From<ErrorMeta> for ApiError {
+    fn from(meta: ErrorMeta) -> Self {
+        ApiError::from_error(meta, None)
+    }
+}
+
 /// An error response payload.
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Default)]
 pub struct ErrorResponse {
@@ -76,10 +204,60 @@ pub struct ErrorMeta {
 }
 
 /// Textual content.
+///
+/// Only the formats requested with [`crate::TextFormat`] are present.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct Text {
     /// Plain text.
-    pub plain: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plain: Option<String>,
     /// Unescaped HTML text.
-    pub html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    /// DOM tree.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dom: Option<DomNode>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(status: u16, message: &str, error: Option<&str>) -> ApiError {
+        let meta = ErrorMeta {
+            status,
+            message: message.to_string(),
+        };
+        let response = error.map(|error| ErrorResponse {
+            error: error.to_string(),
+        });
+        ApiError::from_error(meta, response)
+    }
+
+    #[test]
+    fn test_api_error_classification() {
+        assert!(matches!(
+            error(403, "Requires the vote scope", None),
+            ApiError::Forbidden { .. }
+        ));
+        assert!(matches!(
+            error(403, "Forbidden", Some("insufficient_scope")),
+            ApiError::InsufficientScope { .. }
+        ));
+        assert!(matches!(
+            error(401, "Unauthorized", Some("invalid_token")),
+            ApiError::InvalidToken { .. }
+        ));
+        assert!(matches!(
+            error(401, "Unauthorized", None),
+            ApiError::Unauthorized { .. }
+        ));
+        assert_eq!(
+            error(500, "Oops", Some("boom")),
+            ApiError::Other {
+                error: String::from("500"),
+                message: String::from("Oops (boom)")
+            }
+        );
+    }
 }
@@ -19,6 +19,13 @@ pub struct ReferentsResponse {
     pub referents: Vec<Referent>,
 }
 
+/// A referent response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ReferentResponse {
+    /// A referent.
+    pub referent: Referent,
+}
+
 /// A referent.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct Referent {
@@ -73,7 +80,6 @@ pub struct ReferentCore {
 /// A referent range.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
-
 pub struct Range {
     /// Start.
     #[serde(skip_serializing_if = "Option::is_none")]
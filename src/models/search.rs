@@ -45,7 +45,6 @@ pub struct HitCore<R> {
 /// Search hit index.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
-
 pub enum HitIndex {
     /// A song.
     #[default]
@@ -80,7 +80,6 @@ pub struct UserCore<M> {
 /// User roles.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-
 pub enum Role {
     /// Genius staff.
     Regulator,
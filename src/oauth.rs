@@ -0,0 +1,431 @@
+//! Helpers for the OAuth2 authorization-code flow.
+//!
+//! Visit the [Genius documentation](https://docs.genius.com/#/authentication-h1) for more context.
+use std::{
+    collections::{btree_set::IntoIter, BTreeSet},
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+use reqwest::{Client as ReqwestClient, Error as ReqwestError, Url};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::ParseError;
+
+use crate::{ClientBuilder, BASE_URL};
+
+/// An OAuth scope, which grants access to a set of endpoints.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Access the current user's account.
+    Me,
+    /// Create annotations.
+    CreateAnnotation,
+    /// Update and delete the current user's annotations.
+    ManageAnnotation,
+    /// Vote on annotations.
+    Vote,
+}
+
+impl Scope {
+    /// Get the name of the scope, as used by the API.
+    ///
+    /// # Returns
+    ///
+    /// The name of the scope.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Me => "me",
+            Scope::CreateAnnotation => "create_annotation",
+            Scope::ManageAnnotation => "manage_annotation",
+            Scope::Vote => "vote",
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = UnknownScope;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "me" => Ok(Scope::Me),
+            "create_annotation" => Ok(Scope::CreateAnnotation),
+            "manage_annotation" => Ok(Scope::ManageAnnotation),
+            "vote" => Ok(Scope::Vote),
+            _ => Err(UnknownScope(s.to_string())),
+        }
+    }
+}
+
+/// An unrecognized scope name.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("unknown scope: {0}")]
+pub struct UnknownScope(pub String);
+
+/// A set of [`Scope`]s.
+///
+/// Displayed and parsed as space-separated scope names, like the API expects.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Scopes(BTreeSet<Scope>);
+
+impl Scopes {
+    /// Create an empty set of scopes.
+    ///
+    /// # Returns
+    ///
+    /// An empty [`Scopes`].
+    pub fn new() -> Self {
+        Scopes(BTreeSet::new())
+    }
+
+    /// Create a set of every scope.
+    ///
+    /// # Returns
+    ///
+    /// A [`Scopes`] containing every [`Scope`].
+    pub fn all() -> Self {
+        [
+            Scope::Me,
+            Scope::CreateAnnotation,
+            Scope::ManageAnnotation,
+            Scope::Vote,
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    /// Add a scope to the set.
+    ///
+    /// # Args
+    ///
+    /// * `scope` - The scope to add.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`Scopes`].
+    pub fn with(mut self, scope: Scope) -> Self {
+        self.0.insert(scope);
+        self
+    }
+
+    /// Check if the set contains a scope.
+    ///
+    /// # Args
+    ///
+    /// * `scope` - The scope to check for.
+    ///
+    /// # Returns
+    ///
+    /// Whether the scope is in the set.
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.0.contains(&scope)
+    }
+
+    /// Iterate over the scopes in the set.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the scopes, in a consistent order.
+    pub fn iter(&self) -> impl Iterator<Item = Scope> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl FromIterator<Scope> for Scopes {
+    fn from_iter<I: IntoIterator<Item = Scope>>(iter: I) -> Self {
+        Scopes(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Scopes {
+    type Item = Scope;
+    type IntoIter = IntoIter<Scope>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Display for Scopes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let names = self.iter().map(|s| s.as_str()).collect::<Vec<&str>>();
+        f.write_str(&names.join(" "))
+    }
+}
+
+impl FromStr for Scopes {
+    type Err = UnknownScope;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace().map(Scope::from_str).collect()
+    }
+}
+
+/// An access token granted by the API.
+///
+/// The access token is redacted from the [`Debug`] output.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Token {
+    /// The access token.
+    pub access_token: String,
+    /// The token type, usually "bearer".
+    pub token_type: String,
+    /// The space-separated scopes granted to the token, if the API reported them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl Debug for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Token")
+            .field("access_token", &"[REDACTED]")
+            .field("token_type", &self.token_type)
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+/// A token endpoint response.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenResponse {
+    /// The code was exchanged for a token.
+    Token(Token),
+    /// The code was rejected.
+    Error {
+        /// The name of the error.
+        error: String,
+        /// The description of the error.
+        #[serde(default)]
+        error_description: String,
+    },
+}
+
+/// Errors that can occur during the OAuth flow.
+#[derive(Debug, Error)]
+pub enum OAuthError {
+    /// A general client error.
+    #[error("megamind OAuth error: {0}")]
+    General(#[from] ReqwestError),
+    /// The base URL is invalid.
+    #[error("invalid OAuth URL: {0}")]
+    Url(#[from] ParseError),
+    /// The API rejected the authorization code.
+    #[error("authorization code rejected ({error}): {description}")]
+    Rejected {
+        /// The name of the error.
+        error: String,
+        /// The description of the error.
+        description: String,
+    },
+}
+
+/// An OAuth2 client for the authorization-code flow.
+///
+/// Visit the [Genius API client management page](https://genius.com/api-clients)
+/// to get a client ID and secret, and to register a redirect URI.
+#[derive(Clone)]
+pub struct OAuth {
+    /// client ID
+    client_id: String,
+    /// client secret
+    client_secret: String,
+    /// redirect URI
+    redirect_uri: String,
+    /// base URL for the OAuth endpoints
+    base_url: String,
+    /// internal Reqwest client
+    internal: ReqwestClient,
+}
+
+impl Debug for OAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("OAuth")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"[REDACTED]")
+            .field("redirect_uri", &self.redirect_uri)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OAuth {
+    /// Create a new [`OAuth`] client.
+    ///
+    /// # Args
+    ///
+    /// * `client_id` - The API client ID.
+    /// * `client_secret` - The API client secret.
+    /// * `redirect_uri` - The redirect URI registered with the API client.
+    ///
+    /// # Returns
+    ///
+    /// A new [`OAuth`] client, with the base URL configured to the production API URL.
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Self {
+        OAuth {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: redirect_uri.into(),
+            base_url: String::from(BASE_URL),
+            internal: ReqwestClient::new(),
+        }
+    }
+
+    /// Set the base URL.
+    ///
+    /// Useful for pointing the flow at a local stand-in server.
+    /// The base URL is also passed along to [`OAuth::client_builder`].
+    ///
+    /// # Args
+    ///
+    /// * `base_url` - The base URL for the OAuth endpoints.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`OAuth`] client.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Build the URL that users visit to authorize the API client.
+    ///
+    /// # Args
+    ///
+    /// * `scopes` - The scopes to request.
+    /// * `state` - A value that is passed back to the redirect URI, which should be checked to prevent request forgery.
+    ///
+    /// # Returns
+    ///
+    /// The authorization URL.
+    /// [`OAuthError::Url`] can occur if the base URL is invalid.
+    pub fn authorize_url(
+        &self,
+        scopes: &Scopes,
+        state: &str,
+    ) -> Result<Url, OAuthError> {
+        Ok(Url::parse_with_params(
+            &format!("{}/oauth/authorize", self.base_url),
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("scope", &scopes.to_string()),
+                ("state", state),
+                ("response_type", "code"),
+            ],
+        )?)
+    }
+
+    /// Exchange an authorization code for an access token.
+    ///
+    /// # Args
+    ///
+    /// * `code` - The code passed to the redirect URI.
+    ///
+    /// # Returns
+    ///
+    /// The access token.
+    /// [`OAuthError::Rejected`] can occur if the code is invalid or expired.
+    pub async fn exchange_code(&self, code: &str) -> Result<Token, OAuthError> {
+        let response = self
+            .internal
+            .post(format!("{}/oauth/token", self.base_url))
+            .form(&[
+                ("code", code),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_uri),
+                ("response_type", "code"),
+                ("grant_type", "authorization_code"),
+            ])
+            .send()
+            .await?;
+        match response.json::<TokenResponse>().await? {
+            TokenResponse::Token(token) => Ok(token),
+            TokenResponse::Error {
+                error,
+                error_description,
+            } => Err(OAuthError::Rejected {
+                error,
+                description: error_description,
+            }),
+        }
+    }
+
+    /// Create a [`ClientBuilder`] for an access token.
+    ///
+    /// # Args
+    ///
+    /// * `token` - An access token from [`OAuth::exchange_code`].
+    ///
+    /// # Returns
+    ///
+    /// A [`ClientBuilder`] with the auth token and base URL configured,
+    /// along with the granted scopes if the API reported them.
+    pub fn client_builder(&self, token: &Token) -> ClientBuilder {
+        let builder = ClientBuilder::new()
+            .auth_token(token.access_token.clone())
+            .base_url(self.base_url.clone());
+        match token.scope.as_deref().map(Scopes::from_str) {
+            Some(Ok(scopes)) => builder.scopes(scopes),
+            _ => builder,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_round_trip() {
+        let scopes = "vote me".parse::<Scopes>().unwrap();
+        assert_eq!(scopes, Scopes::new().with(Scope::Me).with(Scope::Vote));
+        assert_eq!(scopes.to_string(), "me vote");
+        assert!("me follow".parse::<Scopes>().is_err());
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let oauth = OAuth::new("id", "secret", "http://localhost/callback")
+            .base_url("http://127.0.0.1:8080/");
+        let url = oauth
+            .authorize_url(&Scopes::all(), "xyz")
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            url,
+            "http://127.0.0.1:8080/oauth/authorize?client_id=id&redirect_uri=http%3A%2F%2Flocalhost%2Fcallback&scope=me+create_annotation+manage_annotation+vote&state=xyz&response_type=code"
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let oauth =
+            OAuth::new("id", String::from("secret"), "http://localhost/callback");
+        let debug = format!("{:?}", oauth);
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("\"secret\""));
+        let token = Token {
+            access_token: String::from("secret"),
+            token_type: String::from("bearer"),
+            scope: None,
+        };
+        let debug = format!("{:?}", token);
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("\"secret\""));
+    }
+}
@@ -0,0 +1,110 @@
+//! Lazy pagination over paged endpoints.
+use std::future::Future;
+
+use futures::{
+    future::{FutureExt, RemoteHandle},
+    stream::{self, Stream, StreamExt},
+};
+
+use crate::ClientError;
+
+/// A page of results.
+#[derive(Debug)]
+pub(crate) struct Page<T> {
+    /// The results on the page.
+    pub(crate) items: Vec<T>,
+    /// The next page offset, if there is one.
+    pub(crate) next_page: Option<u32>,
+}
+
+/// The state of a paginated stream.
+struct PageState<F, T> {
+    /// fetches a page by offset
+    fetch: F,
+    /// next page offset to fetch
+    next_page: Option<u32>,
+    /// whether to fetch the next page while the current one is consumed
+    prefetch: bool,
+    /// handle to a prefetched page
+    prefetched: Option<RemoteHandle<Result<Page<T>, ClientError>>>,
+}
+
+/// Lazily stream every result from a paged endpoint.
+///
+/// Pages are fetched until an empty page arrives, a page has no next offset, or a request fails.
+/// Prefetched pages are fetched on a spawned task, which is cancelled if the stream is dropped.
+///
+/// # Args
+///
+/// * `first_page` - The first page offset.
+/// * `prefetch` - Whether to fetch the next page while the current one is consumed.
+/// * `fetch` - Fetches a page by offset.
+///
+/// # Returns
+///
+/// A stream of results, where a failed page is yielded as a single error.
+pub(crate) fn paginate<T, F, Fut>(
+    first_page: u32,
+    prefetch: bool,
+    fetch: F,
+) -> impl Stream<Item = Result<T, ClientError>>
+where
+    T: Send + 'static,
+    F: Fn(u32) -> Fut,
+    Fut: Future<Output = Result<Page<T>, ClientError>> + Send + 'static,
+{
+    let state = PageState {
+        fetch,
+        next_page: Some(first_page),
+        prefetch,
+        prefetched: None,
+    };
+    stream::unfold(state, |mut state| async move {
+        let page = state.next_page?;
+        let result = match state.prefetched.take() {
+            Some(handle) => handle.await,
+            None => (state.fetch)(page).await,
+        };
+        let items = match result {
+            Ok(page) => {
+                state.next_page = page.next_page.filter(|_| !page.items.is_empty());
+                if let (true, Some(next_page)) = (state.prefetch, state.next_page) {
+                    let (remote, handle) = (state.fetch)(next_page).remote_handle();
+                    tokio::spawn(remote);
+                    state.prefetched = Some(handle);
+                }
+                page.items.into_iter().map(Ok).collect()
+            }
+            Err(e) => {
+                state.next_page = None;
+                vec![Err(e)]
+            }
+        };
+        Some((stream::iter(items), state))
+    })
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_paginate_until_empty() {
+        for prefetch in [false, true] {
+            let results = paginate(1, prefetch, |page| async move {
+                Ok(Page {
+                    items: if page <= 3 { vec![page; 2] } else { Vec::new() },
+                    next_page: Some(page + 1),
+                })
+            })
+            .collect::<Vec<Result<u32, ClientError>>>()
+            .await;
+            let pages = results
+                .into_iter()
+                .map(Result::unwrap)
+                .collect::<Vec<u32>>();
+            assert_eq!(pages, vec![1, 1, 2, 2, 3, 3]);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Client-side rate limiting.
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use tokio::time::sleep;
+
+/// The length of the window for a daily budget.
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A rate limit configuration.
+///
+/// Requests are paced with a token bucket that refills at a steady rate per second,
+/// with an optional budget of requests for every 24 hour window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// refill rate in requests per second
+    per_second: f64,
+    /// bucket capacity
+    burst: u32,
+    /// requests allowed per 24 hour window
+    daily_budget: Option<u32>,
+}
+
+impl RateLimit {
+    /// Create a new [`RateLimit`].
+    ///
+    /// # Args
+    ///
+    /// * `per_second` - The sustained number of requests per second.
+    ///
+    /// # Returns
+    ///
+    /// A new [`RateLimit`], with a burst size equal to the per-second rate and no daily budget.
+    pub fn per_second(per_second: u32) -> Self {
+        let per_second = per_second.max(1);
+        RateLimit {
+            per_second: f64::from(per_second),
+            burst: per_second,
+            daily_budget: None,
+        }
+    }
+
+    /// Create a new [`RateLimit`] that allows one request per interval.
+    ///
+    /// Useful for rates slower than one request per second.
+    ///
+    /// # Args
+    ///
+    /// * `interval` - The time between requests; at least one nanosecond.
+    ///
+    /// # Returns
+    ///
+    /// A new [`RateLimit`], with a burst size of 1 and no daily budget.
+    pub fn every(interval: Duration) -> Self {
+        RateLimit {
+            per_second: 1.0 / interval.max(Duration::from_nanos(1)).as_secs_f64(),
+            burst: 1,
+            daily_budget: None,
+        }
+    }
+
+    /// Set the burst size.
+    ///
+    /// # Args
+    ///
+    /// * `burst` - The maximum number of requests that can be sent back-to-back.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`RateLimit`].
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Set the daily budget.
+    ///
+    /// Once the budget is spent, requests wait for the current 24 hour window to end.
+    ///
+    /// # Args
+    ///
+    /// * `daily_budget` - The maximum number of requests per 24 hour window; at least 1.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`RateLimit`].
+    pub fn daily_budget(mut self, daily_budget: u32) -> Self {
+        self.daily_budget = Some(daily_budget.max(1));
+        self
+    }
+}
+
+/// A token bucket rate limiter, shared between clones.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    /// configuration
+    limit: RateLimit,
+    /// shared bucket state
+    state: Arc<Mutex<BucketState>>,
+}
+
+/// The state of a token bucket.
+#[derive(Debug)]
+struct BucketState {
+    /// available tokens
+    tokens: f64,
+    /// last time tokens were refilled
+    refilled_at: Instant,
+    /// start of the current daily window
+    window_start: Instant,
+    /// requests sent in the current daily window
+    window_used: u32,
+}
+
+impl RateLimiter {
+    /// Create a new [`RateLimiter`] with a full bucket.
+    ///
+    /// # Args
+    ///
+    /// * `limit` - The rate limit configuration.
+    ///
+    /// # Returns
+    ///
+    /// A new [`RateLimiter`].
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        let now = Instant::now();
+        RateLimiter {
+            limit,
+            state: Arc::new(Mutex::new(BucketState {
+                tokens: f64::from(limit.burst),
+                refilled_at: now,
+                window_start: now,
+                window_used: 0,
+            })),
+        }
+    }
+
+    /// Wait until a request is allowed to be sent.
+    pub(crate) async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire(Instant::now()) {
+            debug!(target: "megamind::rate_limit", "waiting {:?} for a token", wait);
+            sleep(wait).await;
+        }
+    }
+
+    /// Try to take a token from the bucket.
+    ///
+    /// # Args
+    ///
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    ///
+    /// Nothing if a token was taken, otherwise how long to wait before trying again.
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(state.refilled_at);
+        state.tokens = (state.tokens + elapsed.as_secs_f64() * self.limit.per_second)
+            .min(f64::from(self.limit.burst));
+        state.refilled_at = now;
+        if let Some(budget) = self.limit.daily_budget {
+            if now.saturating_duration_since(state.window_start) >= DAY {
+                state.window_start = now;
+                state.window_used = 0;
+            }
+            if state.window_used >= budget {
+                return Err((state.window_start + DAY).saturating_duration_since(now));
+            }
+        }
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            state.window_used += 1;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - state.tokens) / self.limit.per_second,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_wait() {
+        let limiter = RateLimiter::new(RateLimit::per_second(2));
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_err());
+        assert!(limiter
+            .try_acquire(now + Duration::from_millis(500))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_daily_budget() {
+        let limiter = RateLimiter::new(RateLimit::per_second(10).daily_budget(1));
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now + Duration::from_secs(1)).is_err());
+        assert!(limiter.try_acquire(now + DAY).is_ok());
+        let limiter = RateLimiter::new(RateLimit::per_second(10).daily_budget(0));
+        assert!(limiter.try_acquire(now).is_ok());
+    }
+
+    #[test]
+    fn test_every() {
+        let limiter = RateLimiter::new(RateLimit::every(Duration::from_secs(5)));
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_ok());
+        let wait = limiter.try_acquire(now).unwrap_err();
+        assert!(wait > Duration::from_millis(4999) && wait <= Duration::from_secs(5));
+        assert!(limiter.try_acquire(now + Duration::from_secs(5)).is_ok());
+    }
+}
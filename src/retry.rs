@@ -0,0 +1,217 @@
+//! Retry policies for transient request failures.
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Error as ReqwestError,
+};
+
+/// A policy for retrying rate limited (429) responses, server errors (5xx),
+/// and transient connection failures.
+///
+/// Delays grow exponentially from the base delay and are capped at the max delay,
+/// with optional jitter to keep concurrent clients from retrying in lockstep.
+/// A `Retry-After` header on a rate limited response takes precedence over the computed delay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// maximum number of attempts, including the first
+    max_attempts: u32,
+    /// delay before the first retry
+    base_delay: Duration,
+    /// upper bound on any single delay
+    max_delay: Duration,
+    /// whether to randomize delays
+    jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Create a new [`RetryPolicy`].
+    ///
+    /// # Args
+    ///
+    /// * `max_attempts` - The maximum number of attempts per request, including the first one.
+    ///
+    /// # Returns
+    ///
+    /// A new [`RetryPolicy`] with a base delay of 500 milliseconds, a max delay of 30 seconds, and jitter enabled.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+
+    /// Create a [`RetryPolicy`] that never retries.
+    ///
+    /// # Returns
+    ///
+    /// A [`RetryPolicy`] with a single attempt.
+    pub fn none() -> Self {
+        RetryPolicy::new(1)
+    }
+
+    /// Set the delay before the first retry.
+    ///
+    /// # Args
+    ///
+    /// * `base_delay` - The base delay; doubled on every subsequent retry.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`RetryPolicy`].
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the upper bound on any single delay.
+    ///
+    /// **Note**: a `Retry-After` header asking for a longer wait than this ends the retries early.
+    ///
+    /// # Args
+    ///
+    /// * `max_delay` - The max delay.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`RetryPolicy`].
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Set whether delays are randomized.
+    ///
+    /// # Args
+    ///
+    /// * `jitter` - Whether to enable jitter.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`RetryPolicy`].
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Get the maximum number of attempts per request.
+    ///
+    /// # Returns
+    ///
+    /// The maximum number of attempts, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Compute the delay before the next attempt.
+    ///
+    /// # Args
+    ///
+    /// * `attempt` - The attempt that just failed, starting at 1.
+    /// * `retry_after` - The delay requested by the server, if any.
+    ///
+    /// # Returns
+    ///
+    /// The delay before the next attempt, or [`None`] if no more attempts should be made.
+    pub(crate) fn delay(
+        &self,
+        attempt: u32,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        if self.jitter {
+            // "equal jitter": keep half of the delay and randomize the other half
+            let half = exponential / 2;
+            Some(half + half.mul_f64(fastrand::f64()))
+        } else {
+            Some(exponential)
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::none()
+    }
+}
+
+/// Parse a `Retry-After` header, which is either a number of seconds or an HTTP date.
+///
+/// # Args
+///
+/// * `headers` - The response headers.
+///
+/// # Returns
+///
+/// The requested delay, if the header is present and valid.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Whether a [`reqwest::Error`] is worth retrying.
+///
+/// # Args
+///
+/// * `error` - The request error.
+///
+/// # Returns
+///
+/// Whether the error came from a failed connection, a timeout, or a dropped request.
+pub(crate) fn is_transient(error: &ReqwestError) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_delay_exponential() {
+        let policy = RetryPolicy::new(4)
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(3))
+            .jitter(false);
+        assert_eq!(policy.delay(1, None), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(2, None), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay(3, None), Some(Duration::from_secs(3)));
+        assert_eq!(policy.delay(4, None), None);
+    }
+
+    #[test]
+    fn test_delay_retry_after() {
+        let policy = RetryPolicy::new(3).max_delay(Duration::from_secs(10));
+        let retry_after = Some(Duration::from_secs(7));
+        assert_eq!(policy.delay(1, retry_after), retry_after);
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(11))), None);
+    }
+
+    #[test]
+    fn test_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+    }
+}
@@ -0,0 +1,396 @@
+//! Per-endpoint request statistics.
+//!
+//! Every [`crate::Client`] collects statistics, which are shared by all of its clones.
+//! Only requests sent to the API count as requests; responses served from the cache
+//! or a replayed cassette are counted separately, and requests that never leave the client
+//! (e.g., [`ClientError::MissingScope`]) aren't counted at all.
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::StatusCode;
+
+use crate::{models::Response, ClientError, Endpoint};
+
+/// The upper bounds of the latency histogram buckets, in milliseconds.
+pub const LATENCY_BUCKETS_MS: [u64; 10] =
+    [10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// Exported counter names and descriptions.
+const COUNTERS: [(&str, &str); 8] = [
+    ("requests", "Requests sent to the API."),
+    ("successes", "Successful responses."),
+    ("api_errors", "API error responses."),
+    ("rate_limited", "Rate limited requests."),
+    ("decode_failures", "Responses that couldn't be decoded."),
+    ("failures", "Requests that failed for other reasons."),
+    ("cache_hits", "Responses served from the cache."),
+    ("replays", "Responses served from a replayed cassette."),
+];
+
+/// Exported token counter names and descriptions.
+const TOKEN_COUNTERS: [(&str, &str); 3] = [
+    ("requests", "Requests sent with the token."),
+    ("rate_limited", "Rate limited responses."),
+    ("unauthorized", "Unauthorized responses."),
+];
+
+/// A latency histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyHistogram {
+    /// counts per bucket, with a final bucket for anything slower than the last bound
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    /// total latency
+    sum: Duration,
+}
+
+impl LatencyHistogram {
+    /// Add a latency to the histogram.
+    ///
+    /// # Args
+    ///
+    /// * `latency` - The latency.
+    fn observe(&mut self, latency: Duration) {
+        let millis = latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| millis <= u128::from(bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.sum += latency;
+    }
+
+    /// Get the number of observed latencies.
+    ///
+    /// # Returns
+    ///
+    /// The number of observed latencies.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Get the total of the observed latencies.
+    ///
+    /// # Returns
+    ///
+    /// The total latency.
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// Get the mean of the observed latencies.
+    ///
+    /// # Returns
+    ///
+    /// The mean latency, or [`None`] if nothing has been observed.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count())
+            .ok()
+            .filter(|&count| count > 0)?;
+        Some(self.sum / count)
+    }
+
+    /// Iterate over the cumulative bucket counts.
+    ///
+    /// # Returns
+    ///
+    /// Each bucket's upper bound paired with the number of latencies at or under it,
+    /// ending with an unbounded ([`None`]) bucket that holds every latency.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        let bounds = LATENCY_BUCKETS_MS
+            .iter()
+            .map(|&bound| Some(Duration::from_millis(bound)))
+            .chain([None]);
+        bounds.zip(self.counts.iter().scan(0, |total, count| {
+            *total += count;
+            Some(*total)
+        }))
+    }
+}
+
+/// Statistics for a single endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EndpointStats {
+    /// Total number of requests sent to the API.
+    pub requests: u64,
+    /// Requests that returned [`Response::Success`].
+    pub successes: u64,
+    /// Requests that returned [`Response::Error`] or [`Response::Other`].
+    pub api_errors: u64,
+    /// Requests that failed with [`ClientError::RateLimited`].
+    pub rate_limited: u64,
+    /// Requests that failed with [`ClientError::Decode`].
+    pub decode_failures: u64,
+    /// Requests that failed for any other reason (e.g., a connection error).
+    pub failures: u64,
+    /// Responses served from the cache, which aren't counted as requests.
+    pub cache_hits: u64,
+    /// Responses served from (or missing from) a replayed cassette, which aren't counted as requests.
+    pub replays: u64,
+    /// Latencies of requests sent to the API, including any retries.
+    pub latency: LatencyHistogram,
+}
+
+impl EndpointStats {
+    /// Get the counter values, in the same order as [`COUNTERS`].
+    ///
+    /// # Returns
+    ///
+    /// The counter values.
+    fn counters(&self) -> [u64; COUNTERS.len()] {
+        [
+            self.requests,
+            self.successes,
+            self.api_errors,
+            self.rate_limited,
+            self.decode_failures,
+            self.failures,
+            self.cache_hits,
+            self.replays,
+        ]
+    }
+}
+
+/// Statistics for a single auth token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenStats {
+    /// Requests sent with the token, including retries.
+    pub requests: u64,
+    /// Requests that got a `429` response.
+    pub rate_limited: u64,
+    /// Requests that got a `401` response.
+    pub unauthorized: u64,
+}
+
+impl TokenStats {
+    /// Get the counter values, in the same order as [`TOKEN_COUNTERS`].
+    ///
+    /// # Returns
+    ///
+    /// The counter values.
+    fn counters(&self) -> [u64; TOKEN_COUNTERS.len()] {
+        [self.requests, self.rate_limited, self.unauthorized]
+    }
+}
+
+/// A snapshot of a client's statistics.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// Statistics by endpoint, for endpoints that have been requested.
+    pub endpoints: BTreeMap<Endpoint, EndpointStats>,
+    /// Statistics by token index, in the order the tokens were given to the builder.
+    pub tokens: BTreeMap<usize, TokenStats>,
+}
+
+impl Stats {
+    /// Get the total number of requests sent to the API across every endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The total number of requests.
+    pub fn requests(&self) -> u64 {
+        self.endpoints.values().map(|stats| stats.requests).sum()
+    }
+
+    /// Export the statistics in the Prometheus text format.
+    ///
+    /// # Returns
+    ///
+    /// The counters and latency histograms, labeled by endpoint, and the token counters, labeled by token index.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        for (i, (name, help)) in COUNTERS.iter().enumerate() {
+            let _ = writeln!(out, "# HELP megamind_{}_total {}", name, help);
+            let _ = writeln!(out, "# TYPE megamind_{}_total counter", name);
+            for (endpoint, stats) in &self.endpoints {
+                let _ = writeln!(
+                    out,
+                    "megamind_{}_total{{endpoint=\"{}\"}} {}",
+                    name,
+                    endpoint.name(),
+                    stats.counters()[i]
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP megamind_request_duration_seconds Request latency, including retries."
+        );
+        let _ = writeln!(out, "# TYPE megamind_request_duration_seconds histogram");
+        for (endpoint, stats) in &self.endpoints {
+            let endpoint = endpoint.name();
+            for (bound, count) in stats.latency.buckets() {
+                let le = bound.map_or(String::from("+Inf"), |bound| {
+                    bound.as_secs_f64().to_string()
+                });
+                let _ = writeln!(
+                    out,
+                    "megamind_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                    endpoint, le, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "megamind_request_duration_seconds_sum{{endpoint=\"{}\"}} {}",
+                endpoint,
+                stats.latency.sum().as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "megamind_request_duration_seconds_count{{endpoint=\"{}\"}} {}",
+                endpoint,
+                stats.latency.count()
+            );
+        }
+        for (i, (name, help)) in TOKEN_COUNTERS.iter().enumerate() {
+            let _ = writeln!(out, "# HELP megamind_token_{}_total {}", name, help);
+            let _ = writeln!(out, "# TYPE megamind_token_{}_total counter", name);
+            for (token, stats) in &self.tokens {
+                let _ = writeln!(
+                    out,
+                    "megamind_token_{}_total{{token=\"{}\"}} {}",
+                    name,
+                    token,
+                    stats.counters()[i]
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Where a response came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResponseSource {
+    /// The API.
+    Network,
+    /// The response cache.
+    Cache,
+    /// A replayed cassette.
+    Cassette,
+}
+
+/// A statistics collector, shared between clones.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StatsCollector(Arc<Mutex<Stats>>);
+
+impl StatsCollector {
+    /// Record the outcome of a request.
+    ///
+    /// # Args
+    ///
+    /// * `endpoint` - The endpoint that was requested.
+    /// * `result` - The result of the request.
+    /// * `source` - Where the response came from.
+    /// * `latency` - How long the request took.
+    pub(crate) fn record<T>(
+        &self,
+        endpoint: Endpoint,
+        result: &Result<Response<T>, ClientError>,
+        source: ResponseSource,
+        latency: Duration,
+    ) {
+        if let Err(ClientError::MissingScope(_)) = result {
+            return;
+        }
+        let mut stats = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = stats.endpoints.entry(endpoint).or_default();
+        match source {
+            ResponseSource::Cache => {
+                stats.cache_hits += 1;
+                return;
+            }
+            ResponseSource::Cassette => {
+                stats.replays += 1;
+                return;
+            }
+            ResponseSource::Network => stats.requests += 1,
+        }
+        match result {
+            Ok(Response::Success { .. }) => stats.successes += 1,
+            Ok(_) => stats.api_errors += 1,
+            Err(ClientError::RateLimited { .. }) => stats.rate_limited += 1,
+            Err(ClientError::Decode(_)) => stats.decode_failures += 1,
+            Err(_) => stats.failures += 1,
+        }
+        stats.latency.observe(latency);
+    }
+
+    /// Record a response to a request sent with a token.
+    ///
+    /// # Args
+    ///
+    /// * `token` - The index of the token.
+    /// * `status` - The response status.
+    pub(crate) fn record_token(&self, token: usize, status: StatusCode) {
+        let mut stats = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = stats.tokens.entry(token).or_default();
+        stats.requests += 1;
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => stats.rate_limited += 1,
+            StatusCode::UNAUTHORIZED => stats.unauthorized += 1,
+            _ => {}
+        }
+    }
+
+    /// Take a snapshot of the statistics.
+    ///
+    /// # Returns
+    ///
+    /// A copy of the current statistics.
+    pub(crate) fn snapshot(&self) -> Stats {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SuccessMeta;
+
+    #[test]
+    fn test_stats_record_and_export() {
+        let collector = StatsCollector::default();
+        let success = Ok(Response::Success {
+            meta: SuccessMeta { status: 200 },
+            response: (),
+        });
+        collector.record(
+            Endpoint::Song,
+            &success,
+            ResponseSource::Network,
+            Duration::from_millis(30),
+        );
+        collector.record(
+            Endpoint::Song,
+            &Err::<Response<()>, _>(ClientError::RateLimited { retry_after: None }),
+            ResponseSource::Network,
+            Duration::from_millis(20_000),
+        );
+        collector.record(
+            Endpoint::Song,
+            &success,
+            ResponseSource::Cache,
+            Duration::from_millis(1),
+        );
+        let stats = collector.snapshot();
+        let song = &stats.endpoints[&Endpoint::Song];
+        assert_eq!(
+            (song.requests, song.successes, song.rate_limited),
+            (2, 1, 1)
+        );
+        assert_eq!(song.cache_hits, 1);
+        assert_eq!(song.latency.mean(), Some(Duration::from_millis(10_015)));
+        let export = stats.to_prometheus();
+        assert!(export.contains("megamind_requests_total{endpoint=\"song\"} 2"));
+        assert!(export.contains(
+            "megamind_request_duration_seconds_bucket{endpoint=\"song\",le=\"0.05\"} 1"
+        ));
+        assert!(export.contains(
+            "megamind_request_duration_seconds_bucket{endpoint=\"song\",le=\"+Inf\"} 2"
+        ));
+    }
+}
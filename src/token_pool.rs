@@ -0,0 +1,295 @@
+//! Rotation across multiple auth tokens.
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use reqwest::header::HeaderValue;
+
+use crate::cache::stable_hash;
+
+/// The default amount of time a token sits out after being rate limited or rejected.
+const DEFAULT_BENCH: Duration = Duration::from_secs(60);
+
+/// How the next token is chosen from a pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenSelection {
+    /// Take turns in the order the tokens were given.
+    #[default]
+    RoundRobin,
+    /// Prefer the token that was rate limited or rejected longest ago (or never),
+    /// taking turns between ties.
+    LeastRecentlyLimited,
+}
+
+/// A token rotation configuration.
+///
+/// Tokens that get a `429` or `401` response are benched, and skipped until the bench time
+/// (or the server's `Retry-After`, if longer) passes.
+/// If every token is benched, the one that comes off the bench soonest is used anyway.
+/// A client with a single token never benches it.
+///
+/// A request that gets a `429` or `401` is sent once more right away with another token
+/// if one is available, even when retries are disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenRotation {
+    /// selection strategy
+    selection: TokenSelection,
+    /// how long a token sits out
+    bench: Duration,
+}
+
+impl Default for TokenRotation {
+    fn default() -> Self {
+        TokenRotation::new(TokenSelection::RoundRobin)
+    }
+}
+
+impl TokenRotation {
+    /// Create a new [`TokenRotation`].
+    ///
+    /// # Args
+    ///
+    /// * `selection` - How the next token is chosen.
+    ///
+    /// # Returns
+    ///
+    /// A new [`TokenRotation`], with a bench time of 60 seconds.
+    pub fn new(selection: TokenSelection) -> Self {
+        TokenRotation {
+            selection,
+            bench: DEFAULT_BENCH,
+        }
+    }
+
+    /// Set the bench time.
+    ///
+    /// # Args
+    ///
+    /// * `bench` - How long a token sits out after a `429` or `401` response.
+    ///
+    /// # Returns
+    ///
+    /// The modified [`TokenRotation`].
+    pub fn bench(mut self, bench: Duration) -> Self {
+        self.bench = bench;
+        self
+    }
+}
+
+/// A pool of authorization headers, shared between clones.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenPool {
+    /// authorization header for each token
+    headers: Arc<[HeaderValue]>,
+    /// stable hash of every token
+    hash: u64,
+    /// configuration
+    rotation: TokenRotation,
+    /// shared rotation state
+    state: Arc<Mutex<PoolState>>,
+}
+
+/// The rotation state of a token pool.
+#[derive(Debug)]
+struct PoolState {
+    /// index to start the next search from
+    next: usize,
+    /// when each token comes off the bench
+    benched_until: Vec<Option<Instant>>,
+    /// when each token was last rate limited or rejected
+    last_limited: Vec<Option<Instant>>,
+}
+
+impl TokenPool {
+    /// Create a new [`TokenPool`] with no tokens benched.
+    ///
+    /// # Args
+    ///
+    /// * `headers` - The authorization header for each token; must not be empty.
+    /// * `rotation` - The rotation configuration.
+    ///
+    /// # Returns
+    ///
+    /// A new [`TokenPool`].
+    pub(crate) fn new(headers: Vec<HeaderValue>, rotation: TokenRotation) -> Self {
+        let len = headers.len();
+        let hash = stable_hash(
+            headers
+                .iter()
+                .flat_map(|header| header.as_bytes().iter().copied().chain([b'\n'])),
+        );
+        TokenPool {
+            headers: headers.into(),
+            hash,
+            rotation,
+            state: Arc::new(Mutex::new(PoolState {
+                next: 0,
+                benched_until: vec![None; len],
+                last_limited: vec![None; len],
+            })),
+        }
+    }
+
+    /// Get a stable hash of every token, which separates cached responses by token.
+    ///
+    /// # Returns
+    ///
+    /// The hash.
+    pub(crate) fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Choose a token for the next request.
+    ///
+    /// # Args
+    ///
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    ///
+    /// The index of the token, which identifies it in logs and stats, and its authorization header.
+    pub(crate) fn select(&self, now: Instant) -> (usize, HeaderValue) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let len = self.headers.len();
+        let mut available = (0..len)
+            .map(|offset| (state.next + offset) % len)
+            .filter(|&i| !matches!(state.benched_until[i], Some(until) if until > now));
+        let selected = match self.rotation.selection {
+            TokenSelection::RoundRobin => available.next(),
+            TokenSelection::LeastRecentlyLimited => {
+                available.min_by_key(|&i| state.last_limited[i])
+            }
+        };
+        let index = selected.unwrap_or_else(|| {
+            let index = (0..len)
+                .min_by_key(|&i| state.benched_until[i])
+                .unwrap_or_default();
+            warn!(
+                target: "megamind::token_pool",
+                "every token is benched, using token {}",
+                index
+            );
+            index
+        });
+        state.next = (index + 1) % len;
+        (index, self.headers[index].clone())
+    }
+
+    /// Check whether a request can be sent again with a different, unbenched token.
+    ///
+    /// # Args
+    ///
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    ///
+    /// Whether the pool has more than one token and any of them is off the bench.
+    pub(crate) fn can_fail_over(&self, now: Instant) -> bool {
+        if self.headers.len() < 2 {
+            return false;
+        }
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .benched_until
+            .iter()
+            .any(|until| !matches!(until, Some(until) if *until > now))
+    }
+
+    /// Bench a token after it was rate limited or rejected.
+    ///
+    /// Does nothing if the pool has a single token, since there's nothing to rotate to.
+    ///
+    /// # Args
+    ///
+    /// * `index` - The index of the token.
+    /// * `now` - The current time.
+    /// * `retry_after` - The delay requested by the server, if any.
+    pub(crate) fn bench(
+        &self,
+        index: usize,
+        now: Instant,
+        retry_after: Option<Duration>,
+    ) {
+        if self.headers.len() < 2 {
+            return;
+        }
+        let bench =
+            retry_after.map_or(self.rotation.bench, |d| d.max(self.rotation.bench));
+        warn!(
+            target: "megamind::token_pool",
+            "benching token {} for {:?}",
+            index,
+            bench
+        );
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.benched_until[index] = Some(now + bench);
+        state.last_limited[index] = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(size: usize, selection: TokenSelection) -> TokenPool {
+        let headers = (0..size)
+            .map(|i| HeaderValue::from_str(&format!("Bearer {}", i)).unwrap())
+            .collect();
+        TokenPool::new(headers, TokenRotation::new(selection))
+    }
+
+    #[test]
+    fn test_round_robin_skips_benched() {
+        let pool = pool(3, TokenSelection::RoundRobin);
+        let now = Instant::now();
+        assert_eq!(pool.select(now).0, 0);
+        pool.bench(1, now, None);
+        assert_eq!(pool.select(now).0, 2);
+        assert_eq!(pool.select(now).0, 0);
+        assert_eq!(pool.select(now + DEFAULT_BENCH).0, 1);
+    }
+
+    #[test]
+    fn test_least_recently_limited() {
+        let pool = pool(3, TokenSelection::LeastRecentlyLimited);
+        let now = Instant::now();
+        pool.bench(0, now, None);
+        pool.bench(
+            2,
+            now + Duration::from_secs(1),
+            Some(Duration::from_secs(120)),
+        );
+        let later = now + DEFAULT_BENCH * 3;
+        assert_eq!(pool.select(later).0, 1);
+        assert_eq!(pool.select(later).0, 1);
+        pool.bench(1, later, None);
+        assert_eq!(pool.select(later).0, 0);
+        assert_eq!(pool.select(later).0, 0);
+    }
+
+    #[test]
+    fn test_single_token_never_benched() {
+        let pool = pool(1, TokenSelection::RoundRobin);
+        let now = Instant::now();
+        pool.bench(0, now, Some(Duration::from_secs(120)));
+        let state = pool.state.lock().unwrap();
+        assert_eq!(state.benched_until, vec![None]);
+        assert_eq!(state.last_limited, vec![None]);
+        drop(state);
+        assert!(!pool.can_fail_over(now));
+    }
+
+    #[test]
+    fn test_can_fail_over() {
+        let pool = pool(2, TokenSelection::RoundRobin);
+        let now = Instant::now();
+        assert!(pool.can_fail_over(now));
+        pool.bench(0, now, None);
+        assert!(pool.can_fail_over(now));
+        pool.bench(1, now, None);
+        assert!(!pool.can_fail_over(now));
+        assert!(pool.can_fail_over(now + DEFAULT_BENCH));
+    }
+}
@@ -0,0 +1,60 @@
+#![cfg(all(feature = "mock", feature = "blocking"))]
+use megamind::{
+    mock::{Fault, MockServer},
+    models::{AlbumTracksResponse, ApiError, SongResponse, Track},
+    ApiResult, ClientError,
+};
+
+fn track(number: u32) -> Track {
+    Track {
+        number: Some(number),
+        ..Track::default()
+    }
+}
+
+#[test]
+fn test_blocking_song() {
+    let server = MockServer::start().unwrap();
+    let mut song = SongResponse::default();
+    song.song.core.essential.id = 1;
+    server.song(1, &song).fault("/songs/2", Fault::NotFound);
+    let client = server.client_builder().build_blocking().unwrap();
+    let response = client.song(1).into_api_result().unwrap();
+    assert_eq!(response.song.core.essential.id, 1);
+    assert!(matches!(
+        client.song(2).into_api_result(),
+        Err(ClientError::Api(ApiError::NotFound { .. }))
+    ));
+    server.assert_requested("GET", "/songs/1", 1);
+}
+
+#[test]
+fn test_blocking_iter_prefetch() {
+    let server = MockServer::start().unwrap();
+    let first = AlbumTracksResponse {
+        tracks: vec![track(1), track(2)],
+        next_page: Some(2),
+    };
+    let last = AlbumTracksResponse {
+        tracks: vec![track(3)],
+        next_page: None,
+    };
+    server
+        .respond_with_query("/albums/1/tracks", &[("page", "1")], &first)
+        .respond_with_query("/albums/1/tracks", &[("page", "2")], &last);
+    let client = server.client_builder().build_blocking().unwrap();
+    let numbers = client
+        .album_tracks_iter(1, Some(2), true)
+        .map(|track| track.unwrap().number.unwrap())
+        .collect::<Vec<u32>>();
+    assert_eq!(numbers, vec![1, 2, 3]);
+    let pages = server
+        .requests()
+        .iter()
+        .map(|request| request.query("page").map(String::from))
+        .collect::<Vec<Option<String>>>();
+    assert_eq!(
+        pages,
+        vec![Some(String::from("1")), Some(String::from("2"))]
+    );
+}
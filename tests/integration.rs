@@ -9,7 +9,10 @@ use rstest::*;
 use serde_json::{from_slice, to_value, Value};
 use tokio::join;
 
-use megamind::{Client, ClientBuilder, ReferentAssociation};
+use megamind::{
+    models::ApiError, ApiResult, ArtistSongsSort, Client, ClientBuilder, ClientError,
+    ReferentAssociation,
+};
 
 #[fixture]
 fn token() -> String {
@@ -56,6 +59,32 @@ async fn test_account(client: Client, reqwest_client: ReqwestClient) {
 #[rstest]
 #[tokio::test]
 
+async fn test_album(
+    #[values(104614, 491200, 999999999)] id: u32,
+    client: Client,
+    reqwest_client: ReqwestClient,
+) {
+    let (result, expected) = join!(
+        client.album(id),
+        reqwest_client
+            .get(format!(
+                "https://api.genius.com/albums/{}?text_format=plain,html",
+                id
+            ))
+            .send()
+            .await
+            .unwrap()
+            .bytes()
+    );
+
+    let result_json = to_value(result.unwrap()).unwrap();
+    let expected_json = from_slice::<Value>(&expected.unwrap()).unwrap();
+    assert_json_include!(actual: result_json, expected: expected_json);
+}
+
+#[rstest]
+#[tokio::test]
+
 async fn test_annotation(
     #[values(16292, 34112, 10225840, 999999999)] id: u32,
     client: Client,
@@ -82,6 +111,17 @@ async fn test_annotation(
 #[rstest]
 #[tokio::test]
 
+async fn test_annotation_not_found(client: Client) {
+    let result = client.annotation(999999999).await.into_api_result();
+    assert!(matches!(
+        result,
+        Err(ClientError::Api(ApiError::NotFound { .. }))
+    ));
+}
+
+#[rstest]
+#[tokio::test]
+
 async fn test_artist(
     #[values(1234, 1421, 13440, 16775, 999999, 999999999)] id: u32,
     client: Client,
@@ -108,6 +148,69 @@ async fn test_artist(
 #[rstest]
 #[tokio::test]
 
+async fn test_artist_songs(
+    #[values(1421, 16775, 999999999)] id: u32,
+    #[values(None, Some(ArtistSongsSort::Title), Some(ArtistSongsSort::Popularity))]
+    sort: Option<ArtistSongsSort>,
+    #[values(None, Some(5))] per_page: Option<u8>,
+    #[values(None, Some(2))] page: Option<u32>,
+    client: Client,
+    reqwest_client: ReqwestClient,
+) {
+    let mut url = format!("https://api.genius.com/artists/{}/songs?", id);
+    if let Some(sort) = sort {
+        let string = match sort {
+            ArtistSongsSort::Title => "sort=title&",
+            ArtistSongsSort::Popularity => "sort=popularity&",
+        };
+        url.push_str(string);
+    }
+    if let Some(pp) = per_page {
+        url.push_str(&format!("per_page={}&", pp));
+    }
+    if let Some(p) = page {
+        url.push_str(&format!("page={}&", p));
+    }
+
+    let (result, expected) = join!(
+        client.artist_songs(id, sort, per_page, page),
+        reqwest_client.get(url).send().await.unwrap().bytes()
+    );
+
+    let result_json = to_value(result.unwrap()).unwrap();
+    let expected_json = from_slice::<Value>(&expected.unwrap()).unwrap();
+    assert_json_eq!(result_json, expected_json);
+}
+
+#[rstest]
+#[tokio::test]
+
+async fn test_referent(
+    #[values(16292, 34112, 999999999)] id: u32,
+    client: Client,
+    reqwest_client: ReqwestClient,
+) {
+    let (result, expected) = join!(
+        client.referent(id),
+        reqwest_client
+            .get(format!(
+                "https://api.genius.com/referents/{}?text_format=plain,html",
+                id
+            ))
+            .send()
+            .await
+            .unwrap()
+            .bytes()
+    );
+
+    let result_json = to_value(result.unwrap()).unwrap();
+    let expected_json = from_slice::<Value>(&expected.unwrap()).unwrap();
+    assert_json_eq!(result_json, expected_json);
+}
+
+#[rstest]
+#[tokio::test]
+
 async fn test_referents(
     #[values(None, Some(12), Some(999999999))] created_by: Option<u32>,
     #[values(
@@ -118,7 +221,7 @@ async fn test_referents(
     )]
     associated: Option<ReferentAssociation>,
     #[values(None, Some(0), Some(5))] per_page: Option<u8>,
-    #[values(None, Some(0), Some(5))] page: Option<u8>,
+    #[values(None, Some(0), Some(5))] page: Option<u32>,
     client: Client,
     reqwest_client: ReqwestClient,
 ) {
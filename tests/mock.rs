@@ -0,0 +1,592 @@
+#![cfg(feature = "mock")]
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use megamind::{
+    cache::{Cache, MemoryStore},
+    cassette::{Cassette, CassetteError},
+    mock::{Fault, MockServer},
+    models::{
+        AccountResponse, AnnotationPayload, AnnotationResponse, ApiError, Referent,
+        ReferentsResponse, Response, SongResponse,
+    },
+    oauth::{OAuth, OAuthError, Scope},
+    rate_limit::RateLimit,
+    retry::RetryPolicy,
+    ApiResult, ClientError, Endpoint, Method, TextFormat,
+};
+
+#[tokio::test]
+async fn test_mock_seeded_song() {
+    let server = MockServer::start().unwrap();
+    let mut song = SongResponse::default();
+    song.song.core.essential.id = 1;
+    song.song.core.essential.title = String::from("Mock Song");
+    server.song(1, &song);
+    let client = server.client_builder().build().unwrap();
+    let response = client.song(1).await.into_api_result().unwrap();
+    assert_eq!(response.song.core.essential, song.song.core.essential);
+    server.assert_requested("GET", "/songs/1", 1);
+    let request = &server.requests()[0];
+    assert_eq!(request.query("text_format"), Some("html,plain"));
+    assert_eq!(request.header("authorization"), Some("Bearer mock"));
+    client
+        .with_text_format([TextFormat::Dom])
+        .song(1)
+        .await
+        .unwrap();
+    assert_eq!(server.requests()[1].query("text_format"), Some("dom"));
+}
+
+#[tokio::test]
+async fn test_mock_faults() {
+    let server = MockServer::start().unwrap();
+    server
+        .fault("/annotations/1", Fault::NotFound)
+        .fault(
+            "/songs/1",
+            Fault::RateLimited {
+                retry_after: Some(3),
+            },
+        )
+        .fault(
+            "/account",
+            Fault::OAuth {
+                error: String::from("invalid_token"),
+                description: String::from("The access token is invalid"),
+            },
+        )
+        .fault("/users/1", Fault::Malformed(String::from("{\"meta\":")));
+    let client = server.client_builder().build().unwrap();
+    assert!(matches!(
+        client.annotation(1).await.into_api_result(),
+        Err(ClientError::Api(ApiError::NotFound { .. }))
+    ));
+    assert!(matches!(
+        client.song(1).await,
+        Err(ClientError::RateLimited {
+            retry_after: Some(_)
+        })
+    ));
+    assert!(matches!(client.account().await, Ok(Response::Other { .. })));
+    assert!(matches!(client.user(1).await, Err(ClientError::Decode(_))));
+}
+
+#[tokio::test]
+async fn test_mock_fault_once() {
+    let server = MockServer::start().unwrap();
+    server
+        .song(1, &SongResponse::default())
+        .fault_once("/songs/1", Fault::NotFound);
+    let client = server.client_builder().build().unwrap();
+    assert!(client.song(1).await.into_api_result().is_err());
+    assert!(client.song(1).await.into_api_result().is_ok());
+    server.assert_requested("GET", "/songs/1", 2);
+}
+
+#[tokio::test]
+async fn test_mock_retries() {
+    let server = MockServer::start().unwrap();
+    let unavailable = || Fault::Status {
+        status: 503,
+        message: String::from("Service unavailable"),
+    };
+    server
+        .song(1, &SongResponse::default())
+        .fault_once("/songs/1", unavailable())
+        .fault_once(
+            "/songs/1",
+            Fault::RateLimited {
+                retry_after: Some(1),
+            },
+        );
+    let client = server
+        .client_builder()
+        .retry(
+            RetryPolicy::new(3)
+                .base_delay(Duration::from_millis(1))
+                .max_delay(Duration::from_secs(5))
+                .jitter(false),
+        )
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    client.song(1).await.into_api_result().unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    server.assert_requested("GET", "/songs/1", 3);
+    server
+        .respond("/annotations", &AnnotationResponse::default())
+        .fault_once("/annotations", unavailable());
+    assert!(matches!(
+        client
+            .create_annotation(&AnnotationPayload::default())
+            .await
+            .into_api_result(),
+        Err(ClientError::Api(_))
+    ));
+    server.assert_requested("POST", "/annotations", 1);
+    server.fault_once(
+        "/songs/1",
+        Fault::RateLimited {
+            retry_after: Some(60),
+        },
+    );
+    let start = Instant::now();
+    assert!(matches!(
+        client.song(1).await,
+        Err(ClientError::RateLimited { retry_after: Some(retry_after) })
+            if retry_after == Duration::from_secs(60)
+    ));
+    assert!(start.elapsed() < Duration::from_secs(5));
+    server.assert_requested("GET", "/songs/1", 4);
+}
+
+#[tokio::test]
+async fn test_mock_rate_limit() {
+    let server = MockServer::start().unwrap();
+    server.song(1, &SongResponse::default());
+    let client = server
+        .client_builder()
+        .rate_limit(RateLimit::every(Duration::from_millis(200)))
+        .build()
+        .unwrap();
+    let clone = client.clone();
+    let start = Instant::now();
+    client.song(1).await.unwrap();
+    clone.song(1).await.unwrap();
+    client.song(1).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    server.assert_requested("GET", "/songs/1", 3);
+}
+
+#[tokio::test]
+async fn test_mock_seeded_pages() {
+    let server = MockServer::start().unwrap();
+    let referent = |id| {
+        let mut referent = Referent::default();
+        referent.core.id = id;
+        referent
+    };
+    server
+        .respond_with_query(
+            "/referents",
+            &[("page", "1")],
+            &ReferentsResponse {
+                referents: vec![referent(1), referent(2)],
+            },
+        )
+        .respond_with_query(
+            "/referents",
+            &[("page", "2")],
+            &ReferentsResponse {
+                referents: vec![referent(3)],
+            },
+        )
+        .respond("/referents", &ReferentsResponse::default());
+    let client = server.client_builder().build().unwrap();
+    let ids = client
+        .referents_stream(None, None, Some(2), false)
+        .map(|referent| referent.unwrap().core.id)
+        .collect::<Vec<u32>>()
+        .await;
+    assert_eq!(ids, vec![1, 2, 3]);
+    server.assert_requested("GET", "/referents", 3);
+    server
+        .respond_once("/songs/1", &SongResponse::default())
+        .fault_once("/songs/1", Fault::NotFound);
+    assert!(client.song(1).await.into_api_result().is_ok());
+    assert!(client.song(1).await.into_api_result().is_err());
+    assert!(client.song(1).await.into_api_result().is_err());
+}
+
+#[tokio::test]
+async fn test_mock_annotation_writes() {
+    let server = MockServer::start().unwrap();
+    let mut annotation = AnnotationResponse::default();
+    annotation.annotation.id = 1;
+    server
+        .respond("/annotations", &annotation)
+        .annotation(1, &annotation);
+    let client = server.client_builder().build().unwrap();
+    let mut payload = AnnotationPayload::default();
+    payload.annotation.body.markdown = String::from("**mock**");
+    let created = client
+        .create_annotation(&payload)
+        .await
+        .into_api_result()
+        .unwrap();
+    assert_eq!(created.annotation.id, 1);
+    client
+        .update_annotation(1, &payload)
+        .await
+        .into_api_result()
+        .unwrap();
+    server.assert_requested("POST", "/annotations", 1);
+    server.assert_requested("PUT", "/annotations/1", 1);
+    let body =
+        serde_json::from_str::<AnnotationPayload>(&server.requests()[1].body).unwrap();
+    assert_eq!(body, payload);
+    server.respond_raw("/annotations/1", "");
+    assert!(matches!(
+        client.delete_annotation(1).await,
+        Ok(Response::Success { response: (), .. })
+    ));
+    server.assert_requested("DELETE", "/annotations/1", 1);
+    server.fault("/annotations/2", Fault::NotFound);
+    assert!(matches!(
+        client.delete_annotation(2).await.into_api_result(),
+        Err(ClientError::Api(ApiError::NotFound { .. }))
+    ));
+}
+
+#[tokio::test]
+async fn test_mock_annotation_votes() {
+    let server = MockServer::start().unwrap();
+    let mut annotation = AnnotationResponse::default();
+    annotation.annotation.id = 1;
+    server
+        .respond("/annotations/1/upvote", &annotation)
+        .respond("/annotations/1/downvote", &annotation)
+        .fault(
+            "/annotations/1/unvote",
+            Fault::Status {
+                status: 403,
+                message: String::from("This action requires the vote scope"),
+            },
+        );
+    let client = server.client_builder().build().unwrap();
+    let upvoted = client.upvote_annotation(1).await.into_api_result().unwrap();
+    assert_eq!(upvoted.annotation.id, 1);
+    client
+        .downvote_annotation(1)
+        .await
+        .into_api_result()
+        .unwrap();
+    let unvoted = client.unvote_annotation(1).await;
+    assert!(matches!(unvoted, Ok(Response::Error { .. })));
+    assert!(matches!(
+        unvoted.into_api_result(),
+        Err(ClientError::Api(ApiError::Forbidden { .. }))
+    ));
+    server.assert_requested("PUT", "/annotations/1/upvote", 1);
+    server.assert_requested("PUT", "/annotations/1/downvote", 1);
+    server.assert_requested("PUT", "/annotations/1/unvote", 1);
+    let scoped = server.client_builder().scopes([Scope::Me]).build().unwrap();
+    assert!(matches!(
+        scoped.upvote_annotation(1).await,
+        Err(ClientError::MissingScope(Scope::Vote))
+    ));
+    server.assert_requested("PUT", "/annotations/1/upvote", 1);
+}
+
+#[tokio::test]
+async fn test_mock_oauth_exchange_code() {
+    let server = MockServer::start().unwrap();
+    let oauth =
+        OAuth::new("id", "secret", "http://localhost/callback").base_url(server.url());
+    server.respond_raw(
+        "/oauth/token",
+        json!({ "access_token": "granted", "token_type": "bearer", "scope": "vote" })
+            .to_string(),
+    );
+    let token = oauth.exchange_code("good").await.unwrap();
+    assert_eq!(token.access_token, "granted");
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert!(request.body.contains("code=good"));
+    assert!(request.body.contains("client_secret=secret"));
+    assert!(request.body.contains("grant_type=authorization_code"));
+    server.song(1, &SongResponse::default());
+    let client = oauth.client_builder(&token).build().unwrap();
+    client.song(1).await.unwrap();
+    assert_eq!(
+        server.requests()[1].header("authorization"),
+        Some("Bearer granted")
+    );
+    assert!(matches!(
+        client.account().await,
+        Err(ClientError::MissingScope(Scope::Me))
+    ));
+    server.fault(
+        "/oauth/token",
+        Fault::OAuth {
+            error: String::from("invalid_grant"),
+            description: String::from("The code is expired"),
+        },
+    );
+    assert!(matches!(
+        oauth.exchange_code("expired").await,
+        Err(OAuthError::Rejected { error, .. }) if error == "invalid_grant"
+    ));
+}
+
+#[tokio::test]
+async fn test_mock_cache() {
+    let server = MockServer::start().unwrap();
+    server
+        .song(1, &SongResponse::default())
+        .account(&AccountResponse::default());
+    let cache = Cache::new(MemoryStore::new(8)).default_ttl(Duration::MAX);
+    let alice = server
+        .client_builder()
+        .auth_token("alice")
+        .cache(cache.clone())
+        .build()
+        .unwrap();
+    let bob = server
+        .client_builder()
+        .auth_token("bob")
+        .cache(cache)
+        .build()
+        .unwrap();
+    alice.song(1).await.unwrap();
+    alice.song(1).await.unwrap();
+    assert_eq!(server.requests().len(), 1);
+    alice.account().await.unwrap();
+    bob.account().await.unwrap();
+    server.assert_requested("GET", "/account", 2);
+    assert_eq!(
+        server.requests()[2].header("authorization"),
+        Some("Bearer bob")
+    );
+    alice.bypass_cache().song(1).await.unwrap();
+    server.assert_requested("GET", "/songs/1", 2);
+}
+
+#[tokio::test]
+async fn test_mock_cassette_matches_body() {
+    let server = MockServer::start().unwrap();
+    server.respond("/annotations", &AnnotationResponse::default());
+    let path = std::env::temp_dir().join(format!(
+        "megamind-mock-cassette-{}.json",
+        std::process::id()
+    ));
+    let mut payload = AnnotationPayload::default();
+    payload.annotation.body.markdown = String::from("recorded");
+    let recorder = server
+        .client_builder()
+        .cassette(Cassette::record(&path))
+        .build()
+        .unwrap();
+    recorder.create_annotation(&payload).await.unwrap();
+    let player = server
+        .client_builder()
+        .cassette(Cassette::replay(&path).unwrap())
+        .build()
+        .unwrap();
+    player
+        .create_annotation(&payload)
+        .await
+        .into_api_result()
+        .unwrap();
+    payload.annotation.body.markdown = String::from("different");
+    assert!(matches!(
+        player.create_annotation(&payload).await,
+        Err(ClientError::Cassette(CassetteError::Unmatched { .. }))
+    ));
+    server.assert_requested("POST", "/annotations", 1);
+    let stats = player.stats();
+    assert_eq!(stats.requests(), 0);
+    assert_eq!(stats.endpoints[&Endpoint::CreateAnnotation].replays, 2);
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn test_mock_cassette_rate_limited() {
+    let server = MockServer::start().unwrap();
+    server.fault(
+        "/songs/1",
+        Fault::RateLimited {
+            retry_after: Some(3),
+        },
+    );
+    let path = std::env::temp_dir().join(format!(
+        "megamind-mock-cassette-429-{}.json",
+        std::process::id()
+    ));
+    let recorder = server
+        .client_builder()
+        .cassette(Cassette::record(&path))
+        .build()
+        .unwrap();
+    let rate_limited = |result| {
+        matches!(
+            result,
+            Err(ClientError::RateLimited { retry_after: Some(retry_after) })
+                if retry_after == Duration::from_secs(3)
+        )
+    };
+    assert!(rate_limited(recorder.song(1).await));
+    let player = server
+        .client_builder()
+        .cassette(Cassette::replay(&path).unwrap())
+        .build()
+        .unwrap();
+    assert!(rate_limited(player.song(1).await));
+    server.assert_requested("GET", "/songs/1", 1);
+    assert_eq!(player.stats().endpoints[&Endpoint::Song].replays, 1);
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn test_mock_batch() {
+    let server = MockServer::start().unwrap();
+    server
+        .song(1, &SongResponse::default())
+        .song(3, &SongResponse::default());
+    let client = server.client_builder().concurrency(2).build().unwrap();
+    let results = client.songs([3, 2, 1]).await;
+    let ids = results.iter().map(|(id, _)| *id).collect::<Vec<u32>>();
+    assert_eq!(ids, vec![3, 2, 1]);
+    assert!(matches!(results[1].1, Ok(Response::Error { .. })));
+    assert!(matches!(results[2].1, Ok(Response::Success { .. })));
+    let mut streamed = client
+        .songs_stream([1, 2, 3])
+        .map(|(id, _)| id)
+        .collect::<Vec<u32>>()
+        .await;
+    streamed.sort();
+    assert_eq!(streamed, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_mock_generic_requests() {
+    let server = MockServer::start().unwrap();
+    server.respond("/songs/1/contributors", &json!({ "contributors": [1, 2] }));
+    let client = server.client_builder().build().unwrap();
+    let value = client
+        .get_value("/songs/1/contributors", &[("per_page", "2")])
+        .await
+        .into_api_result()
+        .unwrap();
+    assert_eq!(value["contributors"], json!([1, 2]));
+    let typed = client
+        .get_json::<Contributors, _, _>("/songs/1/contributors", &[] as &[(&str, &str)])
+        .await
+        .into_api_result()
+        .unwrap();
+    assert_eq!(typed.contributors, vec![1, 2]);
+    let posted = client
+        .request::<Value, _, _, _>(
+            Method::POST,
+            "/songs/1/contributors",
+            &[] as &[(&str, &str)],
+            Some(&json!({ "id": 3 })),
+        )
+        .await;
+    assert!(posted.is_ok());
+    let requests = server.requests();
+    assert_eq!(requests[0].query("per_page"), Some("2"));
+    assert_eq!(requests[0].query("text_format"), None);
+    assert_eq!(requests[2].method, "POST");
+    assert_eq!(requests[2].body, r#"{"id":3}"#);
+}
+
+#[derive(Deserialize)]
+struct Contributors {
+    contributors: Vec<u32>,
+}
+
+#[tokio::test]
+async fn test_mock_transport_options() {
+    let server = MockServer::start().unwrap();
+    server.song(1, &SongResponse::default());
+    let client = server
+        .client_builder()
+        .user_agent("our-service/1.0")
+        .connect_timeout(Duration::from_secs(1))
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    client.song(1).await.unwrap();
+    let http_client = reqwest::Client::builder()
+        .user_agent("our-stack/2.0")
+        .build()
+        .unwrap();
+    let client = server
+        .client_builder()
+        .http_client(http_client)
+        .build()
+        .unwrap();
+    client.song(1).await.unwrap();
+    let requests = server.requests();
+    assert_eq!(requests[0].header("user-agent"), Some("our-service/1.0"));
+    assert_eq!(requests[1].header("user-agent"), Some("our-stack/2.0"));
+    assert_eq!(requests[1].header("authorization"), Some("Bearer mock"));
+}
+
+#[tokio::test]
+async fn test_mock_token_pool() {
+    let server = MockServer::start().unwrap();
+    server
+        .song(1, &SongResponse::default())
+        .fault_once("/songs/1", Fault::RateLimited { retry_after: None })
+        .fault("/songs/2", Fault::RateLimited { retry_after: None });
+    let client = server
+        .client_builder()
+        .auth_tokens(["a", "b", "c"])
+        .build()
+        .unwrap();
+    for _ in 0..3 {
+        client.song(1).await.unwrap();
+    }
+    assert!(matches!(
+        client.song(2).await,
+        Err(ClientError::RateLimited { .. })
+    ));
+    let tokens = server
+        .requests()
+        .iter()
+        .map(|request| request.header("authorization").unwrap().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        tokens,
+        ["Bearer a", "Bearer b", "Bearer c", "Bearer b", "Bearer c", "Bearer b"]
+    );
+    let stats = client.stats();
+    assert_eq!(stats.tokens[&0].rate_limited, 1);
+    assert_eq!(stats.tokens[&1].requests, 3);
+    assert_eq!(stats.tokens[&1].rate_limited, 1);
+}
+
+#[tokio::test]
+async fn test_mock_stats() {
+    let server = MockServer::start().unwrap();
+    server
+        .song(1, &SongResponse::default())
+        .fault("/annotations/1", Fault::NotFound)
+        .fault("/songs/2", Fault::RateLimited { retry_after: None })
+        .fault("/users/1", Fault::Malformed(String::from("{\"meta\":")));
+    let client = server
+        .client_builder()
+        .cache(Cache::new(MemoryStore::new(8)))
+        .build()
+        .unwrap();
+    let clone = client.clone();
+    client.song(1).await.unwrap();
+    clone.song(1).await.unwrap();
+    assert!(client.song(2).await.is_err());
+    client.annotation(1).await.unwrap();
+    assert!(client.user(1).await.is_err());
+    let stats = clone.stats();
+    assert_eq!(stats.requests(), server.requests().len() as u64);
+    let song = &stats.endpoints[&Endpoint::Song];
+    assert_eq!(
+        (
+            song.requests,
+            song.successes,
+            song.rate_limited,
+            song.cache_hits
+        ),
+        (2, 1, 1, 1)
+    );
+    assert_eq!(song.latency.count(), 2);
+    assert_eq!(stats.endpoints[&Endpoint::Annotation].api_errors, 1);
+    assert_eq!(stats.endpoints[&Endpoint::User].decode_failures, 1);
+    let export = stats.to_prometheus();
+    assert!(export.contains("megamind_requests_total{endpoint=\"song\"} 2"));
+    assert!(export.contains("megamind_cache_hits_total{endpoint=\"song\"} 1"));
+}
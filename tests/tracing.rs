@@ -0,0 +1,63 @@
+#![cfg(all(feature = "mock", feature = "tracing"))]
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    subscriber::set_default,
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::Registry, Layer};
+
+use megamind::{mock::MockServer, models::SongResponse};
+
+#[derive(Clone, Default)]
+struct FieldLayer(Arc<Mutex<BTreeMap<String, String>>>);
+
+impl Visit for FieldLayer {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl<S: Subscriber> Layer<S> for FieldLayer {
+    fn on_new_span(&self, span: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+        span.record(&mut self.clone());
+    }
+
+    fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+        values.record(&mut self.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_request_span_fields() {
+    let layer = FieldLayer::default();
+    let _guard = set_default(Registry::default().with(layer.clone()));
+    let server = MockServer::start().unwrap();
+    server.song(7, &SongResponse::default());
+    let client = server.client_builder().build().unwrap();
+    client.song(7).await.unwrap();
+    let fields = layer.0.lock().unwrap();
+    assert_eq!(fields["endpoint"], "song");
+    assert_eq!(fields["genius_id"], "7");
+    assert_eq!(fields["query"], "text_format=html,plain");
+    assert_eq!(fields["status"], "200");
+    assert!(fields.contains_key("response_size"));
+    assert!(fields.contains_key("latency_ms"));
+    assert_eq!(fields["token"], "0");
+}